@@ -0,0 +1,293 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::{pin, Pin},
+    sync::{Arc, OnceLock},
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs `future` to completion on the current thread, parking it while the
+/// future is pending. Enough for tests and sync callers; anything doing real
+/// I/O should be driven by the application's runtime instead.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Resolves to `Err` with the panic payload if polling the inner future panics.
+pub(crate) struct CatchUnwind<F>(pub(crate) F);
+
+impl<F> Future for CatchUnwind<F>
+where
+    F: Future + Unpin,
+{
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Deadlines of pending `Timeout`s, watched by a single thread started on
+/// first use.
+#[derive(Default)]
+struct Timers {
+    deadlines: Mutex<TimerQueue>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct TimerQueue {
+    next_id: u64,
+    wakers: BTreeMap<(Instant, u64), Waker>,
+}
+
+impl Timers {
+    fn global() -> &'static Timers {
+        static TIMERS: OnceLock<Timers> = OnceLock::new();
+        TIMERS.get_or_init(|| {
+            thread::Builder::new()
+                .name("avgr-timer".to_string())
+                .spawn(|| Timers::global().run())
+                .expect("failed to spawn the timer thread");
+            Timers::default()
+        })
+    }
+
+    fn run(&self) {
+        let mut queue = self.deadlines.lock();
+        loop {
+            let now = Instant::now();
+            let mut due = Vec::new();
+            while let Some(entry) = queue.wakers.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                due.push(entry.remove());
+            }
+
+            if !due.is_empty() {
+                MutexGuard::unlocked(&mut queue, || due.into_iter().for_each(Waker::wake));
+                continue;
+            }
+            match queue.wakers.keys().next() {
+                Some(&(deadline, _)) => {
+                    self.changed.wait_until(&mut queue, deadline);
+                }
+                None => self.changed.wait(&mut queue),
+            }
+        }
+    }
+
+    fn schedule(&self, deadline: Instant, waker: &Waker) -> (Instant, u64) {
+        let mut queue = self.deadlines.lock();
+        let key = (deadline, queue.next_id);
+        queue.next_id += 1;
+        queue.wakers.insert(key, waker.clone());
+        if queue.wakers.keys().next() == Some(&key) {
+            self.changed.notify_one();
+        }
+        key
+    }
+
+    fn rearm(&self, key: (Instant, u64), waker: &Waker) {
+        let mut queue = self.deadlines.lock();
+        if let Some(registered) = queue.wakers.get_mut(&key) {
+            if !registered.will_wake(waker) {
+                *registered = waker.clone();
+            }
+        }
+    }
+
+    fn cancel(&self, key: (Instant, u64)) {
+        self.deadlines.lock().wakers.remove(&key);
+    }
+}
+
+/// A deadline registered with the timer thread, cancelled when dropped.
+struct Deadline((Instant, u64));
+
+impl Drop for Deadline {
+    fn drop(&mut self) {
+        Timers::global().cancel(self.0);
+    }
+}
+
+/// Resolves to `None` if the inner future is still pending `after` it was
+/// first polled. Deadlines share one timer thread, which wakes the task once
+/// the deadline passes; it is not a substitute for a runtime's timers.
+pub(crate) struct Timeout<F> {
+    future: F,
+    after: Duration,
+    deadline: Option<Deadline>,
+}
+
+impl<F> Timeout<F> {
+    pub(crate) fn new(future: F, after: Duration) -> Self {
+        Self {
+            future,
+            after,
+            deadline: None,
+        }
+    }
+}
+
+impl<F> Future for Timeout<F>
+where
+    F: Future + Unpin,
+{
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if let Poll::Ready(output) = Pin::new(&mut this.future).poll(cx) {
+            this.deadline = None;
+            return Poll::Ready(Some(output));
+        }
+
+        let timers = Timers::global();
+        match &this.deadline {
+            Some(Deadline(key)) if Instant::now() >= key.0 => {
+                this.deadline = None;
+                return Poll::Ready(None);
+            }
+            Some(Deadline(key)) => timers.rearm(*key, cx.waker()),
+            None => {
+                let key = timers.schedule(Instant::now() + this.after, cx.waker());
+                this.deadline = Some(Deadline(key));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A value set, and its waker woken, from another thread.
+    #[derive(Clone, Default)]
+    struct Slot(Arc<Mutex<(Option<u32>, Option<Waker>)>>);
+
+    impl Slot {
+        fn set(&self, value: u32) {
+            let mut slot = self.0.lock();
+            slot.0 = Some(value);
+            if let Some(waker) = slot.1.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    impl Future for Slot {
+        type Output = u32;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+            let mut slot = self.0.lock();
+            match slot.0.take() {
+                Some(value) => Poll::Ready(value),
+                None => {
+                    slot.1 = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(block_on(async { 40 + 2 }), 42);
+
+        let slot = Slot::default();
+        let remote = slot.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            remote.set(9);
+        });
+
+        assert_eq!(block_on(slot), 9);
+    }
+
+    #[test]
+    fn test_catch_unwind() {
+        let future: BoxFuture<'static, u8> = Box::pin(async { panic!("boom") });
+        let payload = block_on(CatchUnwind(future)).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let after = Duration::from_millis(10);
+        assert_eq!(block_on(Timeout::new(pin!(async { 7 }), after)), Some(7));
+        assert_eq!(
+            block_on(Timeout::new(pin!(std::future::pending::<u8>()), after)),
+            None
+        );
+
+        let slot = Slot::default();
+        let remote = slot.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            remote.set(3);
+        });
+        assert_eq!(
+            block_on(Timeout::new(slot, Duration::from_secs(5))),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_timeout_cancels_its_deadline() {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let pending = pin!(std::future::pending::<u8>());
+        let mut timeout = Timeout::new(pending, Duration::from_secs(60));
+        assert!(Pin::new(&mut timeout).poll(&mut cx).is_pending());
+
+        let key = timeout.deadline.as_ref().unwrap().0;
+        let registered = |key| Timers::global().deadlines.lock().wakers.contains_key(&key);
+        assert!(registered(key));
+        drop(timeout);
+        assert!(!registered(key));
+
+        let mut timeout = Timeout::new(Slot::default(), Duration::from_secs(60));
+        assert!(Pin::new(&mut timeout).poll(&mut cx).is_pending());
+        let key = timeout.deadline.as_ref().unwrap().0;
+        timeout.future.set(1);
+        assert_eq!(Pin::new(&mut timeout).poll(&mut cx), Poll::Ready(Some(1)));
+        assert!(!registered(key));
+    }
+}
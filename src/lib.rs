@@ -1,4 +1,5 @@
 pub mod dependency;
+pub mod executor;
 pub mod route;
 pub mod router;
 pub mod state;
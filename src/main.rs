@@ -2,15 +2,16 @@ use std::sync::{atomic::AtomicU32, Arc};
 
 use avgr::{
     dependency::container::{scoped::system::SystemScope, DependencyContainer},
-    route::handler::Handler,
-    router::StandardRouter,
+    executor::block_on,
+    route::{
+        extract::dep::Dep,
+        handler::{with_extractors, Handler},
+        request::Request,
+    },
+    router::{error::DispatchError, StandardRouter},
 };
 
-fn ping<C: DependencyContainer>(container: C) {
-    let request_counter = container
-        .resolve::<RequestCounter>()
-        .expect("Could not resolve dependency: RequestCounter");
-
+fn ping(request_counter: Dep<RequestCounter>) {
     request_counter
         .count
         .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
@@ -28,36 +29,46 @@ struct StatefulHandler {
 }
 
 impl<C> Handler<(), C> for StatefulHandler {
-    fn handle(&self, _container: C) {
+    fn try_handle(&self, _container: C, _request: &Request) -> Result<(), DispatchError> {
         println!(
             "State: {}",
             self.count.fetch_add(1, std::sync::atomic::Ordering::AcqRel)
         );
+        Ok(())
     }
 }
 
 fn main() {
-    let mut router = StandardRouter::<()>::default();
+    let router = StandardRouter::<()>::default();
     router
         .container
         .register_with_default_scope(SystemScope::Global, RequestCounter::default());
 
-    router.add_route("ping", ping);
-    router.add_route("hello", |_| println!("hello"));
-    router.add_route(
-        "stateful",
-        StatefulHandler {
-            count: AtomicU32::new(0),
-        },
-    );
+    router
+        .add_route("ping", with_extractors(ping))
+        .expect("Failed to add route: ping");
+    router
+        .add_route("hello", |_| println!("hello"))
+        .expect("Failed to add route: hello");
+    router
+        .add_async_route("hello_async", |_| async { println!("hello from async") })
+        .expect("Failed to add route: hello_async");
+    router
+        .add_route(
+            "stateful",
+            StatefulHandler {
+                count: AtomicU32::new(0),
+            },
+        )
+        .expect("Failed to add route: stateful");
 
     let router = Arc::new(router);
 
-    let routes = ["ping", "hello", "stateful"];
+    let routes = ["ping", "hello", "hello_async", "stateful"];
     let mut handles = vec![];
 
     for _ in 0..50 {
-        let route = routes[rand::random::<usize>() % routes.len()].to_string();
+        let route = routes[rand::random::<usize>() % routes.len()];
         let router_clone = router.clone();
 
         handles.push(std::thread::spawn(move || {
@@ -65,7 +76,7 @@ fn main() {
                 &mut rand::thread_rng(),
                 2..=10,
             )));
-            router_clone.dispatch(route).expect("Failed to dispatch");
+            block_on(router_clone.dispatch_async(route)).expect("Failed to dispatch");
         }));
     }
 
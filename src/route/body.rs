@@ -0,0 +1,74 @@
+use std::{
+    any::{self, Any},
+    fmt,
+    sync::Arc,
+};
+
+/// The input carried by a request, of any type. Handlers read it with the
+/// `Input<T>` extractor or `downcast_ref`. Cloning shares the value.
+#[derive(Clone, Default)]
+pub struct Body {
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    type_name: Option<&'static str>,
+}
+
+impl Body {
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self {
+            value: Some(Arc::new(value)),
+            type_name: Some(any::type_name::<T>()),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none()
+    }
+
+    pub fn is<T: Any + Send + Sync>(&self) -> bool {
+        self.downcast_ref::<T>().is_some()
+    }
+
+    pub fn downcast_ref<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.value.as_deref()?.downcast_ref()
+    }
+
+    /// Returns the shared value if it has type `T`.
+    pub fn downcast<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.value.clone()?.downcast().ok()
+    }
+
+    /// Name of the type the body was created with, for error messages.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Body")
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downcast() {
+        let body = Body::new(vec![1u8, 2, 3]);
+
+        assert!(body.is::<Vec<u8>>());
+        assert_eq!(body.downcast_ref::<Vec<u8>>(), Some(&vec![1, 2, 3]));
+        assert!(body.downcast::<String>().is_none());
+        assert_eq!(body.type_name(), Some(any::type_name::<Vec<u8>>()));
+
+        assert_eq!(body.clone().downcast_ref::<Vec<u8>>(), Some(&vec![1, 2, 3]));
+        assert!(Body::empty().is_empty());
+    }
+}
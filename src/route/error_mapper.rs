@@ -0,0 +1,18 @@
+use crate::router::error::HandlerError;
+
+/// Converts an error returned by a fallible handler into the router's output.
+/// Returning `None` leaves the error to the next mapper: the route's mapper
+/// runs first, then its router's, then that of any router it is mounted in.
+/// An error no mapper handles is reported as `DispatchError::Handler`.
+pub trait ErrorMapper<O>: Send + Sync + 'static {
+    fn map_error(&self, error: &HandlerError) -> Option<O>;
+}
+
+impl<F, O> ErrorMapper<O> for F
+where
+    F: Fn(&HandlerError) -> Option<O> + Send + Sync + 'static,
+{
+    fn map_error(&self, error: &HandlerError) -> Option<O> {
+        (self)(error)
+    }
+}
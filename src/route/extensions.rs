@@ -0,0 +1,137 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+type Values = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// A map holding at most one value per type, used to attach arbitrary typed
+/// data to routes and requests. Cloning is cheap: values are shared, and the
+/// map itself is only copied when a clone is modified. An empty map does not
+/// allocate.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    values: Option<Arc<Values>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, returning the value of the same type it replaces.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<Arc<T>> {
+        self.values_mut()
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .and_then(|previous| previous.downcast().ok())
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.values
+            .as_ref()?
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.values
+            .as_ref()
+            .is_some_and(|values| values.contains_key(&TypeId::of::<T>()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.as_ref().map_or(0, |values| values.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn values_mut(&mut self) -> &mut Values {
+        Arc::make_mut(self.values.get_or_insert_with(Default::default))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&TypeId, &Arc<dyn Any + Send + Sync>)> {
+        self.values.iter().flat_map(|values| values.iter())
+    }
+
+    /// Adds every value from `other`, replacing any value of the same type.
+    pub fn extend(&mut self, other: &Extensions) {
+        if self.is_empty() {
+            self.values = other.values.clone();
+            return;
+        }
+        if other.is_empty() {
+            return;
+        }
+
+        let values = self.values_mut();
+        for (key, value) in other.iter() {
+            values.insert(*key, value.clone());
+        }
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Free-form labels attached to a route with `Route::with_tag`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags(pub Vec<String>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct RateLimit(u32);
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.insert(RateLimit(10)).is_none());
+        assert!(extensions.insert("admin").is_none());
+
+        assert_eq!(extensions.get::<RateLimit>(), Some(&RateLimit(10)));
+        assert_eq!(extensions.get::<&str>(), Some(&"admin"));
+        assert!(!extensions.contains::<u8>());
+
+        let previous = extensions.insert(RateLimit(20)).unwrap();
+        assert_eq!(*previous, RateLimit(10));
+        assert_eq!(extensions.len(), 2);
+    }
+
+    #[test]
+    fn test_clones_are_independent() {
+        let mut original = Extensions::new();
+        original.insert(RateLimit(1));
+
+        let mut copy = original.clone();
+        assert_eq!(copy.get::<RateLimit>(), Some(&RateLimit(1)));
+
+        copy.insert(RateLimit(2));
+        assert_eq!(original.get::<RateLimit>(), Some(&RateLimit(1)));
+        assert_eq!(copy.get::<RateLimit>(), Some(&RateLimit(2)));
+    }
+
+    #[test]
+    fn test_extend_overwrites_existing_values() {
+        let mut route = Extensions::new();
+        route.insert(RateLimit(1));
+        route.insert(7u8);
+
+        let mut request = Extensions::new();
+        request.insert(RateLimit(2));
+        request.extend(&route);
+
+        assert_eq!(request.get::<RateLimit>(), Some(&RateLimit(1)));
+        assert_eq!(request.get::<u8>(), Some(&7));
+    }
+}
@@ -0,0 +1,68 @@
+use std::{any::Any, ops::Deref, sync::Arc};
+
+use crate::{
+    dependency::container::DependencyContainer, route::request::Request,
+    router::error::DispatchError,
+};
+
+use super::from_request::FromRequest;
+
+/// A dependency resolved from the router's container. Dispatch fails with
+/// `DispatchError::MissingDependency` if it is not registered.
+#[derive(Debug)]
+pub struct Dep<T>(pub Arc<T>);
+
+impl<T> Clone for Dep<T> {
+    fn clone(&self) -> Self {
+        Dep(self.0.clone())
+    }
+}
+
+impl<T> Deref for Dep<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<C, T> FromRequest<C> for Dep<T>
+where
+    C: DependencyContainer,
+    C::DependencyRef<T>: Into<Arc<T>>,
+    T: Any + Send + Sync,
+{
+    fn from_request(container: &C, _request: &Request) -> Result<Self, DispatchError> {
+        container
+            .resolve::<T>()
+            .map(|dependency| Dep(dependency.into()))
+            .ok_or_else(DispatchError::missing_dependency::<T>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dependency::container::dashmap::DashmapDependencyContainer;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let container = DashmapDependencyContainer::default();
+        container.register(7u32);
+
+        let dep = Dep::<u32>::from_request(&container, &Request::default()).unwrap();
+        assert_eq!(*dep, 7);
+
+        assert!(matches!(
+            Dep::<String>::from_request(&container, &Request::default()),
+            Err(DispatchError::MissingDependency { type_name })
+                if type_name == std::any::type_name::<String>()
+        ));
+        assert!(
+            Option::<Dep<String>>::from_request(&container, &Request::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    InvalidParam {
+        name: String,
+        value: String,
+        expected: &'static str,
+    },
+    WrongNumberOfParams {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidBody {
+        expected: &'static str,
+        actual: Option<&'static str>,
+    },
+    Custom(String),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::InvalidParam {
+                name,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Could not parse parameter {name}: expected {expected}, got {value:?}"
+            ),
+            ExtractError::WrongNumberOfParams { expected, actual } => {
+                write!(f, "Expected {expected} parameter(s), got {actual}")
+            }
+            ExtractError::InvalidBody {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "Expected a body of type {expected}, got {actual}"),
+            ExtractError::InvalidBody {
+                expected,
+                actual: None,
+            } => write!(f, "Expected a body of type {expected}, got none"),
+            ExtractError::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl serde::de::Error for ExtractError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ExtractError::Custom(msg.to_string())
+    }
+}
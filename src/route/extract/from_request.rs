@@ -0,0 +1,49 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    route::{headers::Headers, request::Request},
+    router::error::DispatchError,
+};
+
+use super::{path::Path, query::Query};
+
+/// A handler argument built from the container and the request before the
+/// handler runs. Handlers taking extractors are registered with
+/// `handler::with_extractors`.
+pub trait FromRequest<C>: Sized {
+    fn from_request(container: &C, request: &Request) -> Result<Self, DispatchError>;
+}
+
+impl<C, T> FromRequest<C> for Path<T>
+where
+    T: DeserializeOwned,
+{
+    fn from_request(_container: &C, request: &Request) -> Result<Self, DispatchError> {
+        Ok(Path::from_params(&request.params)?)
+    }
+}
+
+impl<C, T> FromRequest<C> for Query<T>
+where
+    T: DeserializeOwned,
+{
+    fn from_request(_container: &C, request: &Request) -> Result<Self, DispatchError> {
+        Ok(Query::from_query(&request.query)?)
+    }
+}
+
+impl<C> FromRequest<C> for Headers {
+    fn from_request(_container: &C, request: &Request) -> Result<Self, DispatchError> {
+        Ok(request.headers.clone())
+    }
+}
+
+/// Makes any extractor optional: a failed extraction becomes `None`.
+impl<C, T> FromRequest<C> for Option<T>
+where
+    T: FromRequest<C>,
+{
+    fn from_request(container: &C, request: &Request) -> Result<Self, DispatchError> {
+        Ok(T::from_request(container, request).ok())
+    }
+}
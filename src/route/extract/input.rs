@@ -0,0 +1,65 @@
+use std::{
+    any::{self, Any},
+    ops::Deref,
+    sync::Arc,
+};
+
+use crate::{route::request::Request, router::error::DispatchError};
+
+use super::{error::ExtractError, from_request::FromRequest};
+
+/// The request body, as passed to `Router::dispatch_with`. Extraction fails
+/// with `ExtractError::InvalidBody` if the body is missing or has another type.
+#[derive(Debug)]
+pub struct Input<T>(pub Arc<T>);
+
+impl<T> Clone for Input<T> {
+    fn clone(&self) -> Self {
+        Input(self.0.clone())
+    }
+}
+
+impl<T> Deref for Input<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<C, T> FromRequest<C> for Input<T>
+where
+    T: Any + Send + Sync,
+{
+    fn from_request(_container: &C, request: &Request) -> Result<Self, DispatchError> {
+        request.body.downcast().map(Input).ok_or_else(|| {
+            ExtractError::InvalidBody {
+                expected: any::type_name::<T>(),
+                actual: request.body.type_name(),
+            }
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let request = Request::new("/echo").with_body(5u32);
+
+        let input = Input::<u32>::from_request(&(), &request).unwrap();
+        assert_eq!(*input, 5);
+
+        assert!(matches!(
+            Input::<String>::from_request(&(), &request),
+            Err(DispatchError::Extraction(err)) if err == ExtractError::InvalidBody {
+                expected: any::type_name::<String>(),
+                actual: Some(any::type_name::<u32>()),
+            }
+        ));
+        assert!(Input::<u32>::from_request(&(), &Request::new("/echo")).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+mod de;
+pub mod dep;
+pub mod error;
+pub mod from_request;
+pub mod input;
+pub mod path;
+pub mod query;
@@ -0,0 +1,257 @@
+use std::ops::Deref;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+
+use crate::route::path::params::PathParams;
+
+use super::{de::ValueDeserializer, error::ExtractError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+impl<T> Path<T>
+where
+    T: DeserializeOwned,
+{
+    pub fn from_params(params: &PathParams) -> Result<Self, ExtractError> {
+        T::deserialize(ParamsDeserializer { params }).map(Path)
+    }
+}
+
+impl<T> Deref for Path<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct ParamsDeserializer<'a> {
+    params: &'a PathParams,
+}
+
+impl ParamsDeserializer<'_> {
+    fn single(&self) -> Result<ValueDeserializer<'_>, ExtractError> {
+        let mut iter = self.params.iter();
+        match (iter.next(), iter.next()) {
+            (Some((name, value)), None) => Ok(ValueDeserializer::new(name, value)),
+            _ => Err(ExtractError::WrongNumberOfParams {
+                expected: 1,
+                actual: self.params.len(),
+            }),
+        }
+    }
+
+    fn check_len(&self, expected: usize) -> Result<(), ExtractError> {
+        if self.params.len() == expected {
+            Ok(())
+        } else {
+            Err(ExtractError::WrongNumberOfParams {
+                expected,
+                actual: self.params.len(),
+            })
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ParamsDeserializer<'_> {
+    type Error = ExtractError;
+
+    forward_to_single! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.params.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.params
+                .iter()
+                .map(|(name, value)| ValueDeserializer::new(name, value)),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.check_len(len)?;
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(self.params.iter().map(
+            |(name, value)| {
+                (
+                    name.into_deserializer(),
+                    ValueDeserializer::new(name, value),
+                )
+            },
+        )))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> PathParams {
+        let mut params = PathParams::new();
+        for (name, value) in pairs {
+            params.push(*name, *value);
+        }
+        params
+    }
+
+    #[test]
+    fn test_single_value() {
+        let Path(id) = Path::<u64>::from_params(&params(&[("id", "42")])).unwrap();
+        assert_eq!(id, 42);
+    }
+
+    #[test]
+    fn test_tuple() {
+        let Path((id, name)) =
+            Path::<(u64, String)>::from_params(&params(&[("id", "42"), ("name", "bob")])).unwrap();
+        assert_eq!(id, 42);
+        assert_eq!(name, "bob");
+    }
+
+    #[test]
+    fn test_struct() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct UserParams {
+            id: u64,
+            post_id: u32,
+        }
+
+        let path =
+            Path::<UserParams>::from_params(&params(&[("post_id", "7"), ("id", "42")])).unwrap();
+        assert_eq!(path.0, UserParams { id: 42, post_id: 7 });
+    }
+
+    #[test]
+    fn test_enum() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Kind {
+            Posts,
+            Comments,
+        }
+
+        let Path(kind) = Path::<Kind>::from_params(&params(&[("kind", "posts")])).unwrap();
+        assert_eq!(kind, Kind::Posts);
+        assert!(Path::<Kind>::from_params(&params(&[("kind", "likes")])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        assert_eq!(
+            Path::<u64>::from_params(&params(&[("id", "abc")])),
+            Err(ExtractError::InvalidParam {
+                name: "id".to_string(),
+                value: "abc".to_string(),
+                expected: "u64",
+            })
+        );
+    }
+
+    #[test]
+    fn test_wrong_number_of_params() {
+        assert_eq!(
+            Path::<(u64, u64)>::from_params(&params(&[("id", "1")])),
+            Err(ExtractError::WrongNumberOfParams {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            Path::<u64>::from_params(&params(&[("a", "1"), ("b", "2")])),
+            Err(ExtractError::WrongNumberOfParams {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_field() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct UserParams {
+            id: u64,
+            post_id: u32,
+        }
+
+        assert!(matches!(
+            Path::<UserParams>::from_params(&params(&[("id", "42")])),
+            Err(ExtractError::Custom(_))
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+use std::ops::Deref;
+
+use serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+};
+
+use crate::route::path::query::QueryParams;
+
+use super::{de::ValueDeserializer, error::ExtractError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query<T>(pub T);
+
+impl<T> Query<T>
+where
+    T: DeserializeOwned,
+{
+    pub fn from_query(query: &QueryParams) -> Result<Self, ExtractError> {
+        T::deserialize(QueryDeserializer { query }).map(Query)
+    }
+}
+
+impl<T> Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct QueryDeserializer<'a> {
+    query: &'a QueryParams,
+}
+
+impl<'de> de::Deserializer<'de> for QueryDeserializer<'_> {
+    type Error = ExtractError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(self.query.keys().map(
+            |key| {
+                (
+                    key.into_deserializer(),
+                    ValuesDeserializer {
+                        name: key,
+                        values: self.query.get_all(key).collect(),
+                    },
+                )
+            },
+        )))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+struct ValuesDeserializer<'a> {
+    name: &'a str,
+    values: Vec<&'a str>,
+}
+
+impl<'a> ValuesDeserializer<'a> {
+    fn last(&self) -> ValueDeserializer<'a> {
+        ValueDeserializer::new(self.name, self.values.last().copied().unwrap_or_default())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ExtractError> for ValuesDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! forward_to_last {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                de::Deserializer::$method(self.last(), visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValuesDeserializer<'_> {
+    type Error = ExtractError;
+
+    forward_to_last! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_unit deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_unit_struct(self.last(), name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let name = self.name;
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.values
+                .into_iter()
+                .map(|value| ValueDeserializer::new(name, value)),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(self.last(), name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_enum(self.last(), name, variants, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Search {
+        q: String,
+        page: Option<u32>,
+        #[serde(default)]
+        tag: Vec<String>,
+    }
+
+    #[test]
+    fn test_struct() {
+        let query = QueryParams::parse("q=rust+router&page=2&tag=a&tag=b");
+        let Query(search) = Query::<Search>::from_query(&query).unwrap();
+
+        assert_eq!(
+            search,
+            Search {
+                q: "rust router".to_string(),
+                page: Some(2),
+                tag: vec!["a".to_string(), "b".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        let query = QueryParams::parse("q=x");
+        let Query(search) = Query::<Search>::from_query(&query).unwrap();
+
+        assert_eq!(search.page, None);
+        assert!(search.tag.is_empty());
+    }
+
+    #[test]
+    fn test_map() {
+        let query = QueryParams::parse("a=1&b=2");
+        let Query(map) = Query::<HashMap<String, u32>>::from_query(&query).unwrap();
+
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let query = QueryParams::parse("q=x&page=two");
+
+        assert_eq!(
+            Query::<Search>::from_query(&query),
+            Err(ExtractError::InvalidParam {
+                name: "page".to_string(),
+                value: "two".to_string(),
+                expected: "u32",
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let query = QueryParams::parse("page=1");
+        assert!(matches!(
+            Query::<Search>::from_query(&query),
+            Err(ExtractError::Custom(_))
+        ));
+    }
+}
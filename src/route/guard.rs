@@ -0,0 +1,35 @@
+use super::request::Request;
+
+/// A predicate that must hold for a route to match. A route whose guard fails
+/// is skipped and matching moves on to the next candidate.
+///
+/// Guards run before path parameters are extracted, so `request.params` is
+/// empty when they are checked.
+pub trait Guard<C>: Send + Sync + 'static {
+    fn check(&self, container: &C, request: &Request) -> bool;
+}
+
+impl<F, C> Guard<C> for F
+where
+    F: Fn(&C, &Request) -> bool + Send + Sync + 'static,
+{
+    fn check(&self, container: &C, request: &Request) -> bool {
+        (self)(container, request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::route::path::query::QueryParams;
+
+    use super::*;
+
+    #[test]
+    fn test_closure_guard() {
+        let guard = |_: &(), request: &Request| request.query.get("v") == Some("2");
+
+        let request = Request::new("/").with_query(QueryParams::parse("v=2"));
+        assert!(guard.check(&(), &request));
+        assert!(!guard.check(&(), &Request::new("/")));
+    }
+}
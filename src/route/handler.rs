@@ -1,38 +1,448 @@
+use std::{fmt::Display, future::Future, marker::PhantomData};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    executor::BoxFuture,
+    router::error::{DispatchError, HandlerError},
+};
+
+use super::{
+    extract::{from_request::FromRequest, path::Path, query::Query},
+    path::params::PathParams,
+    request::Request,
+};
+
+/// Produces a route's output from the container and the matched request. A
+/// handler that cannot run, for example because an extractor failed, reports
+/// a `DispatchError` instead of panicking.
 pub trait Handler<O, C>: Send + Sync + 'static {
-    fn handle(&self, container: C) -> O;
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError>;
 }
 
 impl<F, O, C> Handler<O, C> for F
 where
     F: Fn(C) -> O + 'static + Send + Sync,
 {
-    fn handle(&self, container: C) -> O {
-        (self)(container)
+    fn try_handle(&self, container: C, _request: &Request) -> Result<O, DispatchError> {
+        Ok((self)(container))
+    }
+}
+
+/// A handler whose output is produced by a future. The request is passed by
+/// value so the future does not borrow from the dispatch.
+pub trait AsyncHandler<O, C>: Send + Sync + 'static {
+    fn handle_async(
+        &self,
+        container: C,
+        request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>>;
+}
+
+impl<F, Fut, O, C> AsyncHandler<O, C> for F
+where
+    F: Fn(C) -> Fut + 'static + Send + Sync,
+    Fut: Future<Output = O> + Send + 'static,
+{
+    fn handle_async(
+        &self,
+        container: C,
+        _request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>> {
+        let future = (self)(container);
+        Box::pin(async move { Ok(future.await) })
+    }
+}
+
+/// Adapts a sync handler to `AsyncHandler`. The handler runs when
+/// `handle_async` is called and the returned future is already complete.
+pub struct FromSync<H>(pub H);
+
+pub fn from_sync<H>(handler: H) -> FromSync<H> {
+    FromSync(handler)
+}
+
+impl<H, O, C> AsyncHandler<O, C> for FromSync<H>
+where
+    H: Handler<O, C>,
+    O: Send + 'static,
+{
+    fn handle_async(
+        &self,
+        container: C,
+        request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>> {
+        Box::pin(std::future::ready(self.0.try_handle(container, &request)))
+    }
+}
+
+/// Adapts a handler returning `Result<O, E>` into one returning `O`. An `Err`
+/// is reported as `DispatchError::Handler`, which an `ErrorMapper` on the
+/// route or router can turn back into an `O`.
+pub struct Fallible<H, E>(H, PhantomData<fn() -> E>);
+
+pub fn fallible<H, E>(handler: H) -> Fallible<H, E> {
+    Fallible(handler, PhantomData)
+}
+
+impl<H, O, E, C> Handler<O, C> for Fallible<H, E>
+where
+    H: Handler<Result<O, E>, C>,
+    E: Display + Send + Sync + 'static,
+{
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        self.0
+            .try_handle(container, request)?
+            .map_err(|err| HandlerError::new(err).into())
+    }
+}
+
+impl<H, O, E, C> AsyncHandler<O, C> for Fallible<H, E>
+where
+    H: AsyncHandler<Result<O, E>, C>,
+    O: Send + 'static,
+    E: Display + Send + Sync + 'static,
+{
+    fn handle_async(
+        &self,
+        container: C,
+        request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>> {
+        let future = self.0.handle_async(container, request);
+        Box::pin(async move { future.await?.map_err(|err| HandlerError::new(err).into()) })
+    }
+}
+
+pub struct WithParams<F>(F);
+
+pub fn with_params<F, O, C>(handler: F) -> WithParams<F>
+where
+    F: Fn(C, &PathParams) -> O + 'static + Send + Sync,
+{
+    WithParams(handler)
+}
+
+impl<F, O, C> Handler<O, C> for WithParams<F>
+where
+    F: Fn(C, &PathParams) -> O + 'static + Send + Sync,
+{
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        Ok((self.0)(container, &request.params))
+    }
+}
+
+pub struct WithPath<F, T>(F, PhantomData<fn() -> T>);
+
+pub fn with_path<F, T, O, C>(handler: F) -> WithPath<F, T>
+where
+    F: Fn(C, Path<T>) -> O + 'static + Send + Sync,
+    T: DeserializeOwned + 'static,
+{
+    WithPath(handler, PhantomData)
+}
+
+impl<F, T, O, C> Handler<O, C> for WithPath<F, T>
+where
+    F: Fn(C, Path<T>) -> O + 'static + Send + Sync,
+    T: DeserializeOwned + 'static,
+{
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        let path = Path::from_params(&request.params)?;
+        Ok((self.0)(container, path))
+    }
+}
+
+pub struct WithQuery<F, T>(F, PhantomData<fn() -> T>);
+
+pub fn with_query<F, T, O, C>(handler: F) -> WithQuery<F, T>
+where
+    F: Fn(C, Query<T>) -> O + 'static + Send + Sync,
+    T: DeserializeOwned + 'static,
+{
+    WithQuery(handler, PhantomData)
+}
+
+impl<F, T, O, C> Handler<O, C> for WithQuery<F, T>
+where
+    F: Fn(C, Query<T>) -> O + 'static + Send + Sync,
+    T: DeserializeOwned + 'static,
+{
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        let query = Query::from_query(&request.query)?;
+        Ok((self.0)(container, query))
     }
 }
 
+pub struct WithRequest<F>(F);
+
+pub fn with_request<F, O, C>(handler: F) -> WithRequest<F>
+where
+    F: Fn(C, &Request) -> O + 'static + Send + Sync,
+{
+    WithRequest(handler)
+}
+
+impl<F, O, C> Handler<O, C> for WithRequest<F>
+where
+    F: Fn(C, &Request) -> O + 'static + Send + Sync,
+{
+    fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        Ok((self.0)(container, request))
+    }
+}
+
+/// A function whose arguments are all extractors, such as
+/// `fn ping(counter: Dep<RequestCounter>, Path(id): Path<u32>)`. Each argument
+/// is built before the function runs; the first one that fails is reported as
+/// the dispatch error. A function returning a future is an `AsyncHandler`.
+pub struct WithExtractors<F, Args>(F, PhantomData<fn() -> Args>);
+
+pub fn with_extractors<F, Args>(handler: F) -> WithExtractors<F, Args> {
+    WithExtractors(handler, PhantomData)
+}
+
+macro_rules! impl_with_extractors {
+    ($($arg:ident),*) => {
+        impl<F, O, C, $($arg,)*> Handler<O, C> for WithExtractors<F, ($($arg,)*)>
+        where
+            F: Fn($($arg),*) -> O + 'static + Send + Sync,
+            $($arg: FromRequest<C> + 'static,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+                $(let $arg = $arg::from_request(&container, request)?;)*
+                Ok((self.0)($($arg),*))
+            }
+        }
+
+        impl<F, Fut, O, C, $($arg,)*> AsyncHandler<O, C> for WithExtractors<F, ($($arg,)*)>
+        where
+            F: Fn($($arg),*) -> Fut + 'static + Send + Sync,
+            Fut: Future<Output = O> + Send + 'static,
+            O: Send + 'static,
+            $($arg: FromRequest<C> + 'static,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            fn handle_async(
+                &self,
+                container: C,
+                request: Request,
+            ) -> BoxFuture<'static, Result<O, DispatchError>> {
+                $(let $arg = match $arg::from_request(&container, &request) {
+                    Ok(value) => value,
+                    Err(err) => return Box::pin(std::future::ready(Err(err))),
+                };)*
+                let future = (self.0)($($arg),*);
+                Box::pin(async move { Ok(future.await) })
+            }
+        }
+    };
+}
+
+impl_with_extractors!();
+impl_with_extractors!(A1);
+impl_with_extractors!(A1, A2);
+impl_with_extractors!(A1, A2, A3);
+impl_with_extractors!(A1, A2, A3, A4);
+impl_with_extractors!(A1, A2, A3, A4, A5);
+impl_with_extractors!(A1, A2, A3, A4, A5, A6);
+impl_with_extractors!(A1, A2, A3, A4, A5, A6, A7);
+impl_with_extractors!(A1, A2, A3, A4, A5, A6, A7, A8);
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use crate::route::path::query::QueryParams;
+
     use super::*;
 
     #[test]
     fn test_handler_with_closure() {
         let handler = |x: i32| x * 2;
-        let result = handler.handle(5);
-        assert_eq!(result, 10);
+        let result = handler.try_handle(5, &Request::default());
+        assert_eq!(result.unwrap(), 10);
     }
 
     #[test]
     fn test_handler_with_string() {
         let handler = |s: String| s.to_uppercase();
-        let result = handler.handle("hello".to_string());
-        assert_eq!(result, "HELLO");
+        let result = handler.try_handle("hello".to_string(), &Request::default());
+        assert_eq!(result.unwrap(), "HELLO");
     }
 
     #[test]
     fn test_handler_with_vec() {
         let handler = |v: Vec<i32>| v.iter().sum::<i32>();
-        let result = handler.handle(vec![1, 2, 3, 4, 5]);
-        assert_eq!(result, 15);
+        let result = handler.try_handle(vec![1, 2, 3, 4, 5], &Request::default());
+        assert_eq!(result.unwrap(), 15);
+    }
+
+    #[test]
+    fn test_handler_with_params() {
+        let handler = with_params(|x: i32, params: &PathParams| {
+            x + params.get("id").map_or(0, |id| id.parse::<i32>().unwrap())
+        });
+
+        let mut params = PathParams::new();
+        params.push("id", "40");
+
+        assert_eq!(
+            handler
+                .try_handle(2, &Request::default().with_params(params.clone()))
+                .unwrap(),
+            42
+        );
+        assert_eq!(handler.try_handle(2, &Request::default()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_plain_handler_ignores_params() {
+        let handler = |x: i32| x * 2;
+        let mut params = PathParams::new();
+        params.push("id", "40");
+
+        assert_eq!(
+            handler
+                .try_handle(5, &Request::default().with_params(params))
+                .unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_handler_with_query() {
+        let handler =
+            with_query(|x: u32, Query(page): Query<HashMap<String, u32>>| x + page["page"]);
+
+        let request = Request::default().with_query(QueryParams::parse("page=40"));
+        assert_eq!(handler.try_handle(2, &request).unwrap(), 42);
+
+        let request = Request::default().with_query(QueryParams::parse("page=forty"));
+        assert!(matches!(
+            handler.try_handle(2, &request),
+            Err(DispatchError::Extraction(_))
+        ));
+    }
+
+    #[test]
+    fn test_handler_with_request() {
+        let handler = with_request(|_: (), request: &Request| {
+            request.query.get_all("tag").collect::<Vec<_>>().join(",")
+        });
+
+        let request = Request::new("/tags").with_query(QueryParams::parse("tag=a&tag=b"));
+        assert_eq!(handler.try_handle((), &request).unwrap(), "a,b");
+    }
+
+    #[test]
+    fn test_handler_with_typed_path() {
+        let handler =
+            with_path(|x: u64, Path((id, name)): Path<(u64, String)>| format!("{name}:{}", x + id));
+
+        let mut params = PathParams::new();
+        params.push("id", "40");
+        params.push("name", "bob");
+        assert_eq!(
+            handler
+                .try_handle(2, &Request::default().with_params(params.clone()))
+                .unwrap(),
+            "bob:42"
+        );
+
+        let mut params = PathParams::new();
+        params.push("id", "forty");
+        params.push("name", "bob");
+        assert!(matches!(
+            handler.try_handle(2, &Request::default().with_params(params.clone())),
+            Err(DispatchError::Extraction(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_extractors() {
+        use crate::{
+            dependency::container::{dashmap::DashmapDependencyContainer, DependencyContainer},
+            route::extract::dep::Dep,
+        };
+
+        fn greet(name: Dep<String>, Path(id): Path<u32>, count: Option<Dep<u8>>) -> String {
+            format!("{} {id} {}", name.as_str(), count.map_or(0, |count| *count))
+        }
+
+        let handler = with_extractors(greet);
+        let container = std::sync::Arc::new(DashmapDependencyContainer::default());
+        let request = Request::default().with_params(PathParams::from_iter([("id", "7")]));
+
+        assert!(matches!(
+            Handler::<String, _>::try_handle(&handler, container.clone(), &request),
+            Err(DispatchError::MissingDependency { type_name })
+                if type_name == std::any::type_name::<String>()
+        ));
+
+        container.register("hi".to_string());
+        assert_eq!(
+            handler.try_handle(container.clone(), &request).unwrap(),
+            "hi 7 0"
+        );
+
+        let no_args = with_extractors(|| 42);
+        assert_eq!(
+            Handler::<i32, ()>::try_handle(&no_args, (), &Request::default()).unwrap(),
+            42
+        );
+    }
+
+    #[test]
+    fn test_fallible_handler() {
+        let handler = fallible(with_params(|x: i32, params: &PathParams| {
+            params
+                .get("id")
+                .unwrap_or("")
+                .parse::<i32>()
+                .map(|id| x + id)
+        }));
+
+        let request = Request::default().with_params(PathParams::from_iter([("id", "40")]));
+        assert_eq!(handler.try_handle(2, &request).unwrap(), 42);
+
+        let request = Request::default().with_params(PathParams::from_iter([("id", "x")]));
+        let Err(DispatchError::Handler(err)) = handler.try_handle(2, &request) else {
+            panic!("expected a handler error");
+        };
+        assert!(err.is::<std::num::ParseIntError>());
+        assert_eq!(err.message(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_async_handlers() {
+        use crate::executor::block_on;
+
+        let handler = |x: i32| async move { x * 2 };
+        assert_eq!(
+            block_on(handler.handle_async(5, Request::default())).unwrap(),
+            10
+        );
+
+        let handler = with_extractors(|Path(id): Path<u32>| async move { id + 1 });
+        let request = Request::default().with_params(PathParams::from_iter([("id", "41")]));
+        assert_eq!(
+            block_on(AsyncHandler::<u32, ()>::handle_async(&handler, (), request)).unwrap(),
+            42
+        );
+        assert!(matches!(
+            block_on(AsyncHandler::<u32, ()>::handle_async(
+                &handler,
+                (),
+                Request::default()
+            )),
+            Err(DispatchError::Extraction(_))
+        ));
+
+        let handler = from_sync(with_params(|x: i32, params: &PathParams| {
+            x + params.len() as i32
+        }));
+        let request = Request::default().with_params(PathParams::from_iter([("id", "1")]));
+        assert_eq!(block_on(handler.handle_async(1, request)).unwrap(), 2);
     }
 }
@@ -0,0 +1,103 @@
+/// Request headers. Names are compared ignoring ASCII case and a name may
+/// appear more than once; insertion order is kept.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value for `name`, keeping any values already present.
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((name.into(), value.into()));
+    }
+
+    /// Replaces every value for `name` with `value`.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        self.remove(&name);
+        self.entries.push((name, value.into()));
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        self.entries.len() != len
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Headers
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_ignore_case() {
+        let mut headers = Headers::from_iter([("Accept", "text/plain"), ("X-Trace", "1")]);
+        headers.append("accept", "application/json");
+
+        assert_eq!(headers.get("ACCEPT"), Some("text/plain"));
+        assert_eq!(
+            headers.get_all("accept").collect::<Vec<_>>(),
+            ["text/plain", "application/json"]
+        );
+
+        headers.insert("x-trace", "2");
+        assert_eq!(headers.get("X-Trace"), Some("2"));
+        assert_eq!(headers.len(), 3);
+
+        assert!(headers.remove("Accept"));
+        assert!(!headers.contains("accept"));
+        assert!(!headers.remove("accept"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+    Custom(String),
+}
+
+impl Method {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::Custom(method) => method,
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "GET" => Method::Get,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "PATCH" => Method::Patch,
+            "DELETE" => Method::Delete,
+            "HEAD" => Method::Head,
+            "OPTIONS" => Method::Options,
+            other => Method::Custom(other.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Method {
+    fn from(value: &str) -> Self {
+        match value.parse() {
+            Ok(method) => method,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Method {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Method {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let method = String::deserialize(deserializer)?;
+        Ok(Method::from(method.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Method::from("GET"), Method::Get);
+        assert_eq!(Method::from("DELETE"), Method::Delete);
+        assert_eq!(
+            Method::from("SUBSCRIBE"),
+            Method::Custom("SUBSCRIBE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for method in [
+            Method::Get,
+            Method::Options,
+            Method::Custom("PURGE".to_string()),
+        ] {
+            assert_eq!(Method::from(method.to_string().as_str()), method);
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let methods = vec![Method::Get, Method::Custom("PURGE".to_string())];
+        let json = serde_json::to_string(&methods).unwrap();
+
+        assert_eq!(json, r#"["GET","PURGE"]"#);
+        assert_eq!(serde_json::from_str::<Vec<Method>>(&json).unwrap(), methods);
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use crate::router::error::DispatchError;
+
+use super::request::Request;
+
+/// Code that runs around handlers, attached to a router with
+/// `Router::with_middleware` or to a single route with
+/// `Route::with_middleware`. A router's middleware also wraps every router
+/// mounted in it.
+///
+/// The route is resolved before any middleware runs, so `before` already sees
+/// its params and extensions, such as its `Tags`. Changing the request path
+/// does not reroute it.
+///
+/// Middleware are entered in the order they were added and left in reverse,
+/// router middleware outside route middleware. `after` sees the handler's
+/// result once error mappers have run.
+pub trait Middleware<O, C>: Send + Sync + 'static {
+    /// Runs before the handler and may modify the request. Returning an error
+    /// skips the handler and everything inside this middleware; the error
+    /// becomes the result seen by the `after` of middleware already entered.
+    fn before(&self, _container: &C, _request: &mut Request) -> Result<(), DispatchError> {
+        Ok(())
+    }
+
+    /// Runs after the handler and may replace its result.
+    fn after(
+        &self,
+        _container: &C,
+        _request: &Request,
+        result: Result<O, DispatchError>,
+    ) -> Result<O, DispatchError> {
+        result
+    }
+}
+
+pub struct Before<F>(F);
+
+/// Middleware that only runs `f` before the handler.
+pub fn before<F, C>(f: F) -> Before<F>
+where
+    F: Fn(&C, &mut Request) -> Result<(), DispatchError> + Send + Sync + 'static,
+{
+    Before(f)
+}
+
+impl<F, O, C> Middleware<O, C> for Before<F>
+where
+    F: Fn(&C, &mut Request) -> Result<(), DispatchError> + Send + Sync + 'static,
+{
+    fn before(&self, container: &C, request: &mut Request) -> Result<(), DispatchError> {
+        (self.0)(container, request)
+    }
+}
+
+pub struct After<F>(F);
+
+/// Middleware that only runs `f` on the handler's result.
+pub fn after<F, O, C>(f: F) -> After<F>
+where
+    F: Fn(&C, &Request, Result<O, DispatchError>) -> Result<O, DispatchError>
+        + Send
+        + Sync
+        + 'static,
+{
+    After(f)
+}
+
+impl<F, O, C> Middleware<O, C> for After<F>
+where
+    F: Fn(&C, &Request, Result<O, DispatchError>) -> Result<O, DispatchError>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn after(
+        &self,
+        container: &C,
+        request: &Request,
+        result: Result<O, DispatchError>,
+    ) -> Result<O, DispatchError> {
+        (self.0)(container, request, result)
+    }
+}
+
+pub(crate) type Layers<O, C> = Vec<Arc<dyn Middleware<O, C>>>;
+
+/// Runs `before` of each layer in order. On error, returns it along with the
+/// number of layers entered, which are the ones `exit` must run.
+pub(crate) fn enter<O, C>(
+    layers: &[Arc<dyn Middleware<O, C>>],
+    container: &C,
+    request: &mut Request,
+) -> Result<(), (usize, DispatchError)>
+where
+    O: 'static,
+    C: 'static,
+{
+    layers
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, layer)| layer.before(container, request).map_err(|err| (index, err)))
+}
+
+/// Runs `after` of each layer, innermost first.
+pub(crate) fn exit<O, C>(
+    layers: &[Arc<dyn Middleware<O, C>>],
+    container: &C,
+    request: &Request,
+    result: Result<O, DispatchError>,
+) -> Result<O, DispatchError>
+where
+    O: 'static,
+    C: 'static,
+{
+    layers.iter().rev().fold(result, |result, layer| {
+        layer.after(container, request, result)
+    })
+}
+
+/// Runs `inner` wrapped in `layers`.
+pub(crate) fn around<O, C>(
+    layers: &[Arc<dyn Middleware<O, C>>],
+    container: &C,
+    request: &mut Request,
+    inner: impl FnOnce(&mut Request) -> Result<O, DispatchError>,
+) -> Result<O, DispatchError>
+where
+    O: 'static,
+    C: 'static,
+{
+    let (entered, result) = match enter(layers, container, request) {
+        Ok(()) => (layers.len(), inner(request)),
+        Err((entered, err)) => (entered, Err(err)),
+    };
+    exit(&layers[..entered], container, request, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    struct Trace {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        reject: bool,
+    }
+
+    impl Middleware<String, ()> for Trace {
+        fn before(&self, _container: &(), _request: &mut Request) -> Result<(), DispatchError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {}", self.name));
+            if self.reject {
+                Err(DispatchError::rejected(self.name))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn after(
+            &self,
+            _container: &(),
+            _request: &Request,
+            result: Result<String, DispatchError>,
+        ) -> Result<String, DispatchError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.name));
+            result
+        }
+    }
+
+    #[test]
+    fn test_around_order_and_short_circuit() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let trace = |name, reject| -> Arc<dyn Middleware<String, ()>> {
+            Arc::new(Trace {
+                name,
+                log: log.clone(),
+                reject,
+            })
+        };
+
+        let layers = [trace("a", false), trace("b", false)];
+        let result = around(&layers, &(), &mut Request::new("/"), |_| {
+            Ok("handler".to_string())
+        });
+        assert_eq!(result.unwrap(), "handler");
+        assert_eq!(
+            log.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            ["before a", "before b", "after b", "after a"]
+        );
+
+        let layers = [trace("a", false), trace("b", true), trace("c", false)];
+        let result = around(&layers, &(), &mut Request::new("/"), |_| {
+            unreachable!("short-circuited")
+        });
+        assert!(matches!(
+            result,
+            Err(DispatchError::Rejected { reason }) if reason == "b"
+        ));
+        assert_eq!(
+            log.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            ["before a", "before b", "after a"]
+        );
+    }
+}
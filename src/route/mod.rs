@@ -1,17 +1,104 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
-use handler::Handler;
+use error_mapper::ErrorMapper;
+use extensions::{Extensions, Tags};
+use guard::Guard;
+use handler::{AsyncHandler, Handler};
+use method::Method;
+use middleware::{Layers, Middleware};
 use path::RoutePath;
+use request::Request;
+use serde::Serialize;
+use serde_json::Value;
 
+use crate::{executor::BoxFuture, router::error::DispatchError};
+
+pub mod body;
+pub mod error_mapper;
+pub mod extensions;
+pub mod extract;
+pub mod guard;
 pub mod handler;
+pub mod headers;
+pub mod method;
+pub mod middleware;
 pub mod path;
+pub mod request;
 
 pub struct Route<P, O, C>
 where
     P: RoutePath,
 {
     pub path: P,
-    handler: Arc<dyn Handler<O, C>>,
+    pub method: Option<Method>,
+    pub name: Option<String>,
+    pub metadata: BTreeMap<String, Value>,
+    pub extensions: Extensions,
+    guards: Vec<Arc<dyn Guard<C>>>,
+    error_mapper: Option<Arc<dyn ErrorMapper<O>>>,
+    middleware: Layers<O, C>,
+    timeout: Option<Duration>,
+    handler: RouteHandler<O, C>,
+}
+
+/// A route's handler. Sync dispatch refuses async handlers with
+/// `DispatchError::RequiresAsync`; async dispatch runs sync handlers in place.
+pub(crate) enum RouteHandler<O, C> {
+    Sync(Arc<dyn Handler<O, C>>),
+    Async(Arc<dyn AsyncHandler<O, C>>),
+}
+
+impl<O, C> Clone for RouteHandler<O, C> {
+    fn clone(&self) -> Self {
+        match self {
+            RouteHandler::Sync(handler) => RouteHandler::Sync(handler.clone()),
+            RouteHandler::Async(handler) => RouteHandler::Async(handler.clone()),
+        }
+    }
+}
+
+impl<O, C> RouteHandler<O, C>
+where
+    O: 'static,
+    C: 'static,
+{
+    pub(crate) fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError> {
+        match self {
+            RouteHandler::Sync(handler) => handler.try_handle(container, request),
+            RouteHandler::Async(_) => Err(DispatchError::RequiresAsync {
+                path: request.path.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn handle_async(
+        &self,
+        container: C,
+        request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>>
+    where
+        O: Send,
+    {
+        match self {
+            RouteHandler::Sync(handler) => {
+                Box::pin(std::future::ready(handler.try_handle(container, &request)))
+            }
+            RouteHandler::Async(handler) => handler.handle_async(container, request),
+        }
+    }
+}
+
+/// A handler-free description of a registered route.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteInfo {
+    pub path: String,
+    pub method: Option<Method>,
+    pub name: Option<String>,
+    pub params: Vec<String>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, Value>,
+    #[serde(skip)]
+    pub extensions: Extensions,
 }
 
 impl<P, O, C> Clone for Route<P, O, C>
@@ -21,6 +108,14 @@ where
     fn clone(&self) -> Self {
         Route {
             path: self.path.clone(),
+            method: self.method.clone(),
+            name: self.name.clone(),
+            metadata: self.metadata.clone(),
+            extensions: self.extensions.clone(),
+            guards: self.guards.clone(),
+            error_mapper: self.error_mapper.clone(),
+            middleware: self.middleware.clone(),
+            timeout: self.timeout,
             handler: self.handler.clone(),
         }
     }
@@ -31,23 +126,189 @@ where
     P: RoutePath,
 {
     pub fn new(path: impl Into<P>, handler: impl Handler<O, C>) -> Route<P, O, C> {
+        Self::from_shared(path, Arc::new(handler))
+    }
+
+    pub fn new_async(path: impl Into<P>, handler: impl AsyncHandler<O, C>) -> Route<P, O, C> {
+        Self::from_handler(path, RouteHandler::Async(Arc::new(handler)))
+    }
+
+    pub(crate) fn from_shared(path: impl Into<P>, handler: Arc<dyn Handler<O, C>>) -> Self {
+        Self::from_handler(path, RouteHandler::Sync(handler))
+    }
+
+    fn from_handler(path: impl Into<P>, handler: RouteHandler<O, C>) -> Self {
         Route {
             path: path.into(),
-            handler: Arc::new(handler),
+            method: None,
+            name: None,
+            metadata: BTreeMap::new(),
+            extensions: Extensions::new(),
+            guards: Vec::new(),
+            error_mapper: None,
+            middleware: Vec::new(),
+            timeout: None,
+            handler,
+        }
+    }
+
+    pub fn with_method(mut self, method: Option<Method>) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_extension<T: std::any::Any + Send + Sync>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        let mut tags = self.extensions.get::<Tags>().cloned().unwrap_or_default();
+        tags.0.push(tag.into());
+        self.extensions.insert(tags);
+        self
+    }
+
+    pub fn tags(&self) -> &[String] {
+        self.extensions
+            .get::<Tags>()
+            .map_or(&[], |tags| tags.0.as_slice())
+    }
+
+    pub fn with_guard(mut self, guard: impl Guard<C>) -> Self {
+        self.guards.push(Arc::new(guard));
+        self
+    }
+
+    /// Maps this route's handler errors before the router's mapper sees them.
+    pub fn with_error_mapper(mut self, mapper: impl ErrorMapper<O>) -> Self {
+        self.error_mapper = Some(Arc::new(mapper));
+        self
+    }
+
+    pub(crate) fn error_mapper(&self) -> Option<&Arc<dyn ErrorMapper<O>>> {
+        self.error_mapper.as_ref()
+    }
+
+    /// Wraps this route's handler, inside any middleware of the router.
+    pub fn with_middleware(mut self, middleware: impl Middleware<O, C>) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub(crate) fn middleware(&self) -> &Layers<O, C> {
+        &self.middleware
+    }
+
+    /// Fails async dispatch with `DispatchError::TimedOut` if the handler has
+    /// not finished `after` it started. Overrides the router's timeout.
+    pub fn with_timeout(mut self, after: Duration) -> Self {
+        self.timeout = Some(after);
+        self
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Takes whatever this route leaves unset (name, metadata, extensions,
+    /// error mapper, middleware and timeout) from `replaced`, so replacing a
+    /// route's handler keeps the rest of its configuration.
+    pub(crate) fn inherit(mut self, replaced: Option<&Route<P, O, C>>) -> Self {
+        let Some(replaced) = replaced else {
+            return self;
+        };
+        if self.name.is_none() {
+            self.name = replaced.name.clone();
+        }
+        if self.metadata.is_empty() {
+            self.metadata = replaced.metadata.clone();
+        }
+        if self.extensions.is_empty() {
+            self.extensions = replaced.extensions.clone();
+        }
+        if self.error_mapper.is_none() {
+            self.error_mapper = replaced.error_mapper.clone();
+        }
+        if self.middleware.is_empty() {
+            self.middleware = replaced.middleware.clone();
+        }
+        self.timeout = self.timeout.or(replaced.timeout);
+        self
+    }
+
+    pub fn is_guarded(&self) -> bool {
+        !self.guards.is_empty()
+    }
+
+    pub fn check_guards(&self, container: &C, request: &Request) -> bool
+    where
+        C: 'static,
+    {
+        self.guards
+            .iter()
+            .all(|guard| guard.check(container, request))
+    }
+
+    pub fn info(&self) -> RouteInfo {
+        RouteInfo {
+            path: self.path.string_repr(),
+            method: self.method.clone(),
+            name: self.name.clone(),
+            params: self
+                .path
+                .pattern()
+                .map(|pattern| pattern.param_names().map(str::to_string).collect())
+                .unwrap_or_default(),
+            tags: self.tags().to_vec(),
+            metadata: self.metadata.clone(),
+            extensions: self.extensions.clone(),
         }
     }
 
-    pub fn handle(&self, container: C) -> O
+    pub fn is_async(&self) -> bool {
+        matches!(self.handler, RouteHandler::Async(_))
+    }
+
+    pub(crate) fn handler(&self) -> &RouteHandler<O, C> {
+        &self.handler
+    }
+
+    pub fn try_handle(&self, container: C, request: &Request) -> Result<O, DispatchError>
     where
         C: 'static,
         O: 'static,
     {
-        self.handler.handle(container)
+        self.handler.try_handle(container, request)
+    }
+
+    pub fn handle_async(
+        &self,
+        container: C,
+        request: Request,
+    ) -> BoxFuture<'static, Result<O, DispatchError>>
+    where
+        C: 'static,
+        O: Send + 'static,
+    {
+        self.handler.handle_async(container, request)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::executor::block_on;
+
     use super::*;
 
     #[test]
@@ -55,7 +316,7 @@ mod tests {
         let route: Route<String, _, _> = Route::new("/test", |_| 42);
 
         assert_eq!(route.path.as_str(), "/test");
-        assert_eq!(route.handle(()), 42);
+        assert_eq!(route.try_handle((), &Request::default()).unwrap(), 42);
     }
 
     #[test]
@@ -64,6 +325,46 @@ mod tests {
         let cloned = route.clone();
 
         assert_eq!(route.path.as_str(), cloned.path.as_str());
-        assert_eq!(route.handle(()), cloned.handle(()));
+        assert_eq!(
+            route.try_handle((), &Request::default()).unwrap(),
+            cloned.try_handle((), &Request::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_async_route() {
+        let route: Route<String, _, _> = Route::new_async("/test", |x: u8| async move { x + 1 });
+
+        assert!(route.is_async());
+        assert!(matches!(
+            route.try_handle(41, &Request::new("/test")),
+            Err(DispatchError::RequiresAsync { path }) if path == "/test"
+        ));
+        assert_eq!(
+            block_on(route.handle_async(1, Request::default())).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_route_info() {
+        let route: Route<String, u8, ()> = Route::new("/users/:id", |_| 42)
+            .with_method(Some(Method::Get))
+            .with_name("user")
+            .with_metadata("auth", true)
+            .with_tag("users")
+            .with_extension(30u32);
+
+        let info = route.info();
+        assert_eq!(info.path, "/users/:id");
+        assert_eq!(info.method, Some(Method::Get));
+        assert_eq!(info.name.as_deref(), Some("user"));
+        assert_eq!(info.params, ["id"]);
+        assert_eq!(info.tags, ["users"]);
+        assert_eq!(
+            info.metadata,
+            BTreeMap::from([("auth".to_string(), Value::Bool(true))])
+        );
+        assert_eq!(info.extensions.get::<u32>(), Some(&30));
     }
 }
@@ -1,5 +1,18 @@
+pub mod encoding;
+pub mod params;
+pub mod pattern;
+pub mod query;
+
+use pattern::PathPattern;
+
+use crate::router::error::PatternError;
+
 pub trait RoutePath: Clone {
     fn string_repr(&self) -> String;
+
+    fn pattern(&self) -> Result<PathPattern, PatternError> {
+        PathPattern::parse(&self.string_repr())
+    }
 }
 
 impl RoutePath for String {
@@ -0,0 +1,96 @@
+use super::encoding::percent_decode;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct PathParams {
+    params: Vec<(String, String)>,
+}
+
+impl PathParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.params.push((name.into(), value.into()));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Percent-decodes every value, as URLs built by `Router::url_for` encode
+    /// them.
+    pub(crate) fn decode(&mut self) {
+        for (_, value) in &mut self.params {
+            if value.contains('%') {
+                *value = percent_decode(value);
+            }
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for PathParams
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            params: iter
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_get() {
+        let mut params = PathParams::new();
+        params.push("id", "42");
+        params.push("post_id", "7");
+
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.get("post_id"), Some("7"));
+        assert_eq!(params.get("missing"), None);
+        assert_eq!(params.len(), 2);
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let params = PathParams::from_iter([("id", "42"), ("post_id", "7")]);
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_preserves_order() {
+        let mut params = PathParams::new();
+        params.push("a", "1");
+        params.push("b", "2");
+
+        let collected: Vec<_> = params.iter().collect();
+        assert_eq!(collected, vec![("a", "1"), ("b", "2")]);
+    }
+}
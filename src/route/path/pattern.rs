@@ -0,0 +1,366 @@
+use itertools::Itertools;
+
+use crate::router::error::{PatternError, UrlError};
+
+use super::{encoding::percent_encode, params::PathParams};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Static(String),
+    Param(String),
+    Wildcard,
+    CatchAll(String),
+}
+
+impl Segment {
+    pub fn rank(&self) -> u8 {
+        match self {
+            Segment::Static(_) => 0,
+            Segment::Param(_) | Segment::Wildcard => 1,
+            Segment::CatchAll(_) => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+pub(crate) fn split_segments(path: &str) -> impl Iterator<Item = &str> + Clone {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    (!path.is_empty())
+        .then(|| path.split('/'))
+        .into_iter()
+        .flatten()
+}
+
+impl PathPattern {
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        let segments = split_segments(pattern)
+            .map(|segment| {
+                if segment == "*" {
+                    return Ok(Segment::Wildcard);
+                }
+                if let Some(name) = segment.strip_prefix('*') {
+                    return Ok(Segment::CatchAll(name.to_string()));
+                }
+                match segment.strip_prefix(':') {
+                    Some("") => Err(PatternError::EmptyParamName {
+                        pattern: pattern.to_string(),
+                    }),
+                    Some(name) => Ok(Segment::Param(name.to_string())),
+                    None => Ok(Segment::Static(segment.to_string())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(position) = segments
+            .iter()
+            .position(|segment| matches!(segment, Segment::CatchAll(_)))
+        {
+            if position != segments.len() - 1 {
+                return Err(PatternError::CatchAllNotLast {
+                    pattern: pattern.to_string(),
+                });
+            }
+        }
+
+        let parsed = Self { segments };
+        if let Some(name) = parsed.param_names().duplicates().next() {
+            return Err(PatternError::DuplicateParamName {
+                pattern: pattern.to_string(),
+                name: name.to_string(),
+            });
+        }
+        Ok(parsed)
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Static(_)))
+    }
+
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Param(name) | Segment::CatchAll(name) => Some(name.as_str()),
+            Segment::Static(_) | Segment::Wildcard => None,
+        })
+    }
+
+    /// Ranks used to order overlapping patterns, compared left to right. A lower
+    /// rank is more specific: static beats param (or `*`), param beats catch-all.
+    pub fn specificity(&self) -> Vec<u8> {
+        self.segments.iter().map(Segment::rank).collect()
+    }
+
+    pub fn is_ambiguous_with(&self, other: &PathPattern) -> bool {
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(&other.segments)
+                .all(|(a, b)| match (a, b) {
+                    (Segment::Static(a), Segment::Static(b)) => a == b,
+                    (
+                        Segment::Param(_) | Segment::Wildcard,
+                        Segment::Param(_) | Segment::Wildcard,
+                    ) => true,
+                    (Segment::CatchAll(_), Segment::CatchAll(_)) => true,
+                    _ => false,
+                })
+    }
+
+    pub fn build(&self, params: &PathParams) -> Result<String, UrlError> {
+        if let Some((name, _)) = params
+            .iter()
+            .find(|(name, _)| !self.param_names().any(|known| known == *name))
+        {
+            return Err(UrlError::ExtraParam(name.to_string()));
+        }
+
+        let mut url = String::new();
+        for segment in &self.segments {
+            url.push('/');
+            match segment {
+                Segment::Static(value) => url.push_str(value),
+                Segment::Param(name) => {
+                    let value = params
+                        .get(name)
+                        .ok_or_else(|| UrlError::MissingParam(name.clone()))?;
+                    url.push_str(&percent_encode(value));
+                }
+                Segment::Wildcard => return Err(UrlError::UnnamedWildcard),
+                Segment::CatchAll(name) => {
+                    let value = params
+                        .get(name)
+                        .ok_or_else(|| UrlError::MissingParam(name.clone()))?;
+                    let encoded: Vec<_> = value.split('/').map(percent_encode).collect();
+                    url.push_str(&encoded.join("/"));
+                }
+            }
+        }
+
+        if url.is_empty() {
+            url.push('/');
+        }
+        Ok(url)
+    }
+
+    pub fn matches(&self, path: &str) -> Option<PathParams> {
+        self.match_segments(path, false)
+    }
+
+    /// Like `matches`, but compares static segments ignoring ASCII case.
+    /// Captured values keep the case they have in `path`.
+    pub fn matches_ignore_case(&self, path: &str) -> Option<PathParams> {
+        self.match_segments(path, true)
+    }
+
+    fn match_segments(&self, path: &str, ignore_case: bool) -> Option<PathParams> {
+        let mut params = PathParams::new();
+        let mut parts = split_segments(path);
+
+        for segment in &self.segments {
+            let part = parts.next()?;
+            match segment {
+                Segment::Static(value) if value == part => {}
+                Segment::Static(value) if ignore_case && value.eq_ignore_ascii_case(part) => {}
+                Segment::Static(_) => return None,
+                Segment::Param(_) | Segment::Wildcard if part.is_empty() => return None,
+                Segment::Param(name) => params.push(name.as_str(), part),
+                Segment::Wildcard => {}
+                Segment::CatchAll(name) => {
+                    params.push(name.as_str(), std::iter::once(part).chain(parts).join("/"));
+                    return Some(params);
+                }
+            }
+        }
+
+        match parts.next() {
+            Some(_) => None,
+            None => Some(params),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern = PathPattern::parse("/users/:id/posts/:post_id").unwrap();
+        assert_eq!(
+            pattern.segments(),
+            &[
+                Segment::Static("users".to_string()),
+                Segment::Param("id".to_string()),
+                Segment::Static("posts".to_string()),
+                Segment::Param("post_id".to_string()),
+            ]
+        );
+        assert!(!pattern.is_static());
+        assert_eq!(pattern.param_names().collect::<Vec<_>>(), ["id", "post_id"]);
+    }
+
+    #[test]
+    fn test_match_captures_params() {
+        let pattern = PathPattern::parse("/users/:id/posts/:post_id").unwrap();
+
+        let params = pattern.matches("/users/42/posts/7").unwrap();
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.get("post_id"), Some("7"));
+
+        assert!(pattern.matches("/users/42/posts").is_none());
+        assert!(pattern.matches("/users/42/comments/7").is_none());
+        assert!(pattern.matches("/users/42/posts/7/extra").is_none());
+        assert!(pattern.matches("/users//posts/7").is_none());
+        assert!(pattern.matches("/users/42/posts/").is_none());
+    }
+
+    #[test]
+    fn test_matches_ignore_case() {
+        let pattern = PathPattern::parse("/users/:id").unwrap();
+
+        assert!(pattern.matches("/Users/Bob").is_none());
+        let params = pattern.matches_ignore_case("/Users/Bob").unwrap();
+        assert_eq!(params.get("id"), Some("Bob"));
+    }
+
+    #[test]
+    fn test_leading_slash_is_optional() {
+        let pattern = PathPattern::parse("ping").unwrap();
+        assert!(pattern.is_static());
+        assert!(pattern.matches("/ping").is_some());
+        assert!(pattern.matches("ping").is_some());
+    }
+
+    #[test]
+    fn test_wildcard_matches_single_segment() {
+        let pattern = PathPattern::parse("/files/*/meta").unwrap();
+        assert!(pattern.matches("/files/a/meta").is_some());
+        assert!(pattern.matches("/files/a/b/meta").is_none());
+        assert!(pattern.matches("/files/meta").is_none());
+    }
+
+    #[test]
+    fn test_catch_all_captures_rest() {
+        let pattern = PathPattern::parse("/static/*rest").unwrap();
+        assert_eq!(pattern.param_names().collect::<Vec<_>>(), ["rest"]);
+
+        let params = pattern.matches("/static/css/site.css").unwrap();
+        assert_eq!(params.get("rest"), Some("css/site.css"));
+
+        let params = pattern.matches("/static/logo.png").unwrap();
+        assert_eq!(params.get("rest"), Some("logo.png"));
+
+        assert!(pattern.matches("/static").is_none());
+        assert!(pattern.matches("/other/logo.png").is_none());
+    }
+
+    #[test]
+    fn test_catch_all_must_be_last() {
+        assert_eq!(
+            PathPattern::parse("/static/*rest/more"),
+            Err(PatternError::CatchAllNotLast {
+                pattern: "/static/*rest/more".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_param_names() {
+        assert_eq!(
+            PathPattern::parse("/b/:"),
+            Err(PatternError::EmptyParamName {
+                pattern: "/b/:".to_string()
+            })
+        );
+        assert_eq!(
+            PathPattern::parse("/a/:id/:id"),
+            Err(PatternError::DuplicateParamName {
+                pattern: "/a/:id/:id".to_string(),
+                name: "id".to_string()
+            })
+        );
+        assert_eq!(
+            PathPattern::parse("/a/:rest/*rest"),
+            Err(PatternError::DuplicateParamName {
+                pattern: "/a/:rest/*rest".to_string(),
+                name: "rest".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_specificity() {
+        let static_pattern = PathPattern::parse("/users/me").unwrap();
+        let param_pattern = PathPattern::parse("/users/:id").unwrap();
+        let catch_all_pattern = PathPattern::parse("/users/*rest").unwrap();
+
+        assert!(static_pattern.specificity() < param_pattern.specificity());
+        assert!(param_pattern.specificity() < catch_all_pattern.specificity());
+    }
+
+    #[test]
+    fn test_ambiguity() {
+        let pattern = PathPattern::parse("/a/:x/*rest").unwrap();
+
+        assert!(pattern.is_ambiguous_with(&PathPattern::parse("/a/:x/*rest").unwrap()));
+        assert!(pattern.is_ambiguous_with(&PathPattern::parse("/a/:y/*other").unwrap()));
+        assert!(pattern.is_ambiguous_with(&PathPattern::parse("/a/*/*rest").unwrap()));
+        assert!(!pattern.is_ambiguous_with(&PathPattern::parse("/a/b/*rest").unwrap()));
+        assert!(!pattern.is_ambiguous_with(&PathPattern::parse("/a/:x/:y").unwrap()));
+        assert!(!pattern.is_ambiguous_with(&PathPattern::parse("/a/:x").unwrap()));
+    }
+
+    #[test]
+    fn test_build() {
+        let pattern = PathPattern::parse("users/:id/files/*path").unwrap();
+        let params = PathParams::from_iter([("id", "42 a"), ("path", "docs/read me.md")]);
+
+        assert_eq!(
+            pattern.build(&params),
+            Ok("/users/42%20a/files/docs/read%20me.md".to_string())
+        );
+        assert_eq!(
+            PathPattern::parse("/").unwrap().build(&PathParams::new()),
+            Ok("/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let pattern = PathPattern::parse("/users/:id").unwrap();
+
+        assert_eq!(
+            pattern.build(&PathParams::new()),
+            Err(UrlError::MissingParam("id".to_string()))
+        );
+        assert_eq!(
+            pattern.build(&PathParams::from_iter([("id", "1"), ("extra", "2")])),
+            Err(UrlError::ExtraParam("extra".to_string()))
+        );
+        assert_eq!(
+            PathPattern::parse("/files/*")
+                .unwrap()
+                .build(&PathParams::new()),
+            Err(UrlError::UnnamedWildcard)
+        );
+    }
+
+    #[test]
+    fn test_root() {
+        let pattern = PathPattern::parse("/").unwrap();
+        assert!(pattern.segments().is_empty());
+        assert!(pattern.matches("/").is_some());
+        assert!(pattern.matches("").is_some());
+        assert!(pattern.matches("/a").is_none());
+    }
+}
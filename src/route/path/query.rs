@@ -0,0 +1,106 @@
+use super::encoding::form_decode;
+
+pub fn split_query(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct QueryParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(query: &str) -> Self {
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (form_decode(key), form_decode(value))
+            })
+            .collect();
+
+        Self { pairs }
+    }
+
+    pub fn push(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.pairs.push((key.into(), value.into()));
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.pairs
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let mut seen = Vec::new();
+        self.pairs.iter().filter_map(move |(key, _)| {
+            if seen.contains(&key) {
+                return None;
+            }
+            seen.push(key);
+            Some(key.as_str())
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_query() {
+        assert_eq!(split_query("/search?q=x"), ("/search", Some("q=x")));
+        assert_eq!(split_query("/search?"), ("/search", Some("")));
+        assert_eq!(split_query("/search"), ("/search", None));
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = QueryParams::parse("q=hello+world&tag=a&tag=b%26c&flag&=empty");
+
+        assert_eq!(query.get("q"), Some("hello world"));
+        assert_eq!(query.get_all("tag").collect::<Vec<_>>(), ["a", "b&c"]);
+        assert_eq!(query.get("flag"), Some(""));
+        assert_eq!(query.get(""), Some("empty"));
+        assert_eq!(query.get("missing"), None);
+        assert_eq!(query.keys().collect::<Vec<_>>(), ["q", "tag", "flag", ""]);
+        assert_eq!(query.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert!(QueryParams::parse("").is_empty());
+        assert!(QueryParams::parse("&&").is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use std::any::Any;
+
+use super::{
+    body::Body,
+    extensions::Extensions,
+    headers::Headers,
+    method::Method,
+    path::{params::PathParams, query::QueryParams},
+};
+
+#[derive(Default, Debug, Clone)]
+pub struct Request {
+    pub method: Option<Method>,
+    pub path: String,
+    pub params: PathParams,
+    pub query: QueryParams,
+    pub headers: Headers,
+    pub body: Body,
+    /// Typed values available to handlers. Dispatch adds the matched route's
+    /// extensions, overwriting values of a type already present.
+    pub extensions: Extensions,
+}
+
+impl Request {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_method(mut self, method: Option<Method>) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_params(mut self, params: PathParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_query(mut self, query: QueryParams) -> Self {
+        self.query = query;
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    pub fn with_headers(mut self, headers: Headers) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn with_body<T: Any + Send + Sync>(mut self, body: T) -> Self {
+        self.body = Body::new(body);
+        self
+    }
+
+    pub fn with_extension<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+}
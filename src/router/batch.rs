@@ -0,0 +1,168 @@
+use std::marker::PhantomData;
+
+use crate::{
+    dependency::container::DependencyContainer,
+    route::{
+        handler::{AsyncHandler, Handler},
+        method::Method,
+        path::RoutePath,
+        Route,
+    },
+    storage::{error::RouteConflict, RouteStorage},
+};
+
+use super::table::RouteTable;
+
+/// Route changes collected by `Router::batch` and published together.
+pub struct RouteBatch<'a, S, P, O, C> {
+    table: &'a mut RouteTable<S>,
+    _p: PhantomData<P>,
+    _o: PhantomData<O>,
+    _c: PhantomData<C>,
+}
+
+impl<'a, S, P, O, C> RouteBatch<'a, S, P, O, C>
+where
+    S: RouteStorage<P, O, C>,
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    pub(crate) fn new(table: &'a mut RouteTable<S>) -> Self {
+        Self {
+            table,
+            _p: PhantomData,
+            _o: PhantomData,
+            _c: PhantomData,
+        }
+    }
+
+    pub fn insert_route(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict> {
+        self.table.insert(route)
+    }
+
+    pub fn add_route(
+        &mut self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.insert_route(Route::new(path, handler))
+    }
+
+    pub fn add_async_route(
+        &mut self,
+        path: impl Into<P>,
+        handler: impl AsyncHandler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.insert_route(Route::new_async(path, handler))
+    }
+
+    pub fn add_named_route(
+        &mut self,
+        name: impl Into<String>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.insert_route(Route::new(path, handler).with_name(name))
+    }
+
+    pub fn add_method_route(
+        &mut self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.insert_route(Route::new(path, handler).with_method(Some(method.into())))
+    }
+
+    /// Replaces the route registered for `route`'s method and path with
+    /// `route`, which keeps the replaced route's name and configuration
+    /// unless it sets its own.
+    pub fn swap_route(
+        &mut self,
+        route: Route<P, O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.table.replace(route)
+    }
+
+    pub fn replace_route(
+        &mut self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.swap_route(Route::new(path, handler))
+    }
+
+    pub fn replace_method_route(
+        &mut self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.swap_route(Route::new(path, handler).with_method(Some(method.into())))
+    }
+
+    pub fn remove_route(&mut self, path: impl Into<P>) -> Option<Route<P, O, C>> {
+        self.table.remove(None, &path.into().string_repr())
+    }
+
+    pub fn remove_method_route(
+        &mut self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+    ) -> Option<Route<P, O, C>> {
+        self.table
+            .remove(Some(&method.into()), &path.into().string_repr())
+    }
+
+    pub fn remove_named_route(&mut self, name: &str) -> Option<Route<P, O, C>> {
+        self.table.remove_named(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        route::{handler::with_params, path::params::PathParams},
+        router::RadixRouter,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_batch_registers_many_routes() {
+        let router: RadixRouter<String> = RadixRouter::default();
+
+        router
+            .batch(|batch| {
+                (0..5000).try_for_each(|i| {
+                    batch.add_route(
+                        format!("/items/{i}/:part"),
+                        with_params(move |_, params: &PathParams| {
+                            format!("{i}/{}", params.get("part").unwrap())
+                        }),
+                    )
+                })
+            })
+            .unwrap();
+
+        assert_eq!(router.routes().len(), 5000);
+        assert_eq!(router.dispatch("/items/0/a"), Some("0/a".to_string()));
+        assert_eq!(router.dispatch("/items/4999/b"), Some("4999/b".to_string()));
+    }
+
+    #[test]
+    fn test_failed_batch_publishes_nothing() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router.add_route("/taken", |_| "taken".to_string()).unwrap();
+
+        let result = router.batch(|batch| {
+            batch.add_route("/fresh", |_| "fresh".to_string())?;
+            batch.remove_route("/taken");
+            batch.add_route("/fresh", |_| "again".to_string())
+        });
+
+        assert!(matches!(result, Err(RouteConflict::Duplicate { .. })));
+        assert_eq!(router.dispatch("/fresh"), None);
+        assert_eq!(router.dispatch("/taken"), Some("taken".to_string()));
+    }
+}
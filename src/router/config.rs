@@ -0,0 +1,198 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    route::{
+        handler::Handler,
+        method::Method,
+        path::{pattern::PathPattern, RoutePath},
+        Route,
+    },
+    storage::error::RouteConflict,
+};
+
+use super::error::PatternError;
+
+/// Handlers that a [`RouterConfig`] can refer to by name.
+pub struct HandlerRegistry<O, C> {
+    handlers: HashMap<String, Arc<dyn Handler<O, C>>>,
+}
+
+impl<O, C> Default for HandlerRegistry<O, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O, C> HandlerRegistry<O, C> {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, handler: impl Handler<O, C>) {
+        self.handlers.insert(name.into(), Arc::new(handler));
+    }
+
+    pub fn with(mut self, name: impl Into<String>, handler: impl Handler<O, C>) -> Self {
+        self.register(name, handler);
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouterConfig {
+    pub routes: Vec<RouteConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    pub path: String,
+    pub handler: String,
+    #[serde(default)]
+    pub method: Option<Method>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, Value>,
+}
+
+impl RouterConfig {
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(json).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+
+    pub(crate) fn resolve<P, O, C>(
+        &self,
+        registry: &HandlerRegistry<O, C>,
+    ) -> Result<Vec<Route<P, O, C>>, ConfigError>
+    where
+        P: RoutePath + From<String>,
+    {
+        self.routes
+            .iter()
+            .map(|config| {
+                let handler = registry.handlers.get(&config.handler).ok_or_else(|| {
+                    ConfigError::UnknownHandler {
+                        path: config.path.clone(),
+                        handler: config.handler.clone(),
+                    }
+                })?;
+
+                PathPattern::parse(&config.path).map_err(ConfigError::InvalidPattern)?;
+
+                let mut route = Route::from_shared(config.path.clone(), handler.clone())
+                    .with_method(config.method.clone());
+                route.name = config.name.clone();
+                route.metadata = config.metadata.clone();
+                Ok(config
+                    .tags
+                    .iter()
+                    .fold(route, |route, tag| route.with_tag(tag)))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Parse(String),
+    UnknownHandler { path: String, handler: String },
+    InvalidPattern(PatternError),
+    Conflict(RouteConflict),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Parse(err) => write!(f, "Invalid router config: {err}"),
+            ConfigError::UnknownHandler { path, handler } => {
+                write!(f, "Route {path} refers to unknown handler {handler}")
+            }
+            ConfigError::InvalidPattern(err) => write!(f, "Invalid router config: {err}"),
+            ConfigError::Conflict(conflict) => write!(f, "Invalid router config: {conflict}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<RouteConflict> for ConfigError {
+    fn from(conflict: RouteConflict) -> Self {
+        ConfigError::Conflict(conflict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = RouterConfig::from_json(
+            r#"{
+                "routes": [
+                    { "path": "/users/:id", "method": "GET", "handler": "get_user", "name": "user" },
+                    { "path": "/health", "handler": "health", "tags": ["ops"], "metadata": { "public": true } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.routes.len(), 2);
+        assert_eq!(config.routes[0].method, Some(Method::Get));
+        assert_eq!(config.routes[0].name.as_deref(), Some("user"));
+        assert_eq!(config.routes[1].method, None);
+        assert_eq!(config.routes[1].tags, ["ops"]);
+        assert_eq!(config.routes[1].metadata["public"], Value::Bool(true));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_fields() {
+        let err = RouterConfig::from_json(
+            r#"{ "routes": [{ "path": "/", "handler": "a", "verb": "GET" }] }"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+    }
+
+    #[test]
+    fn test_unknown_handler() {
+        let registry = HandlerRegistry::<String, ()>::new().with("known", |_| String::new());
+        let config = RouterConfig::from_json(
+            r#"{ "routes": [
+                { "path": "/a", "handler": "known" },
+                { "path": "/b", "handler": "missing" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let err = config.resolve::<String, _, _>(&registry).err().unwrap();
+        assert_eq!(
+            err,
+            ConfigError::UnknownHandler {
+                path: "/b".to_string(),
+                handler: "missing".to_string()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Route /b refers to unknown handler missing"
+        );
+    }
+}
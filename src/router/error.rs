@@ -0,0 +1,220 @@
+use std::{
+    any::{type_name, Any},
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+
+use itertools::Itertools;
+
+use crate::route::{extract::error::ExtractError, method::Method};
+
+#[derive(Debug, Clone)]
+pub enum DispatchError {
+    NotFound {
+        path: String,
+    },
+    MethodNotAllowed {
+        method: Option<Method>,
+        path: String,
+        allowed: Vec<Method>,
+    },
+    Extraction(ExtractError),
+    Redirect {
+        location: String,
+    },
+    MissingDependency {
+        type_name: &'static str,
+    },
+    HandlerPanicked {
+        path: String,
+        message: String,
+    },
+    /// The route has an async handler and was dispatched synchronously.
+    RequiresAsync {
+        path: String,
+    },
+    TimedOut {
+        path: String,
+        after: Duration,
+    },
+    Rejected {
+        reason: String,
+    },
+    Handler(HandlerError),
+}
+
+impl DispatchError {
+    pub fn missing_dependency<T: ?Sized>() -> Self {
+        DispatchError::MissingDependency {
+            type_name: type_name::<T>(),
+        }
+    }
+
+    pub fn rejected(reason: impl Into<String>) -> Self {
+        DispatchError::Rejected {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::NotFound { path } => write!(f, "No route matches {path}"),
+            DispatchError::MethodNotAllowed {
+                method,
+                path,
+                allowed,
+            } => write!(
+                f,
+                "Method {} is not allowed for {path}, allowed: {}",
+                method.as_ref().map_or("<none>", Method::as_str),
+                allowed.iter().join(", ")
+            ),
+            DispatchError::Extraction(err) => write!(f, "Failed to extract request data: {err}"),
+            DispatchError::Redirect { location } => write!(f, "Route moved to {location}"),
+            DispatchError::MissingDependency { type_name } => {
+                write!(f, "No dependency of type {type_name} is registered")
+            }
+            DispatchError::HandlerPanicked { path, message } => {
+                write!(f, "Handler for {path} panicked: {message}")
+            }
+            DispatchError::RequiresAsync { path } => {
+                write!(f, "Handler for {path} is async and needs async dispatch")
+            }
+            DispatchError::TimedOut { path, after } => {
+                write!(f, "Handler for {path} timed out after {after:?}")
+            }
+            DispatchError::Rejected { reason } => write!(f, "Request rejected: {reason}"),
+            DispatchError::Handler(err) => write!(f, "Handler failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DispatchError::Extraction(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ExtractError> for DispatchError {
+    fn from(err: ExtractError) -> Self {
+        DispatchError::Extraction(err)
+    }
+}
+
+impl From<HandlerError> for DispatchError {
+    fn from(err: HandlerError) -> Self {
+        DispatchError::Handler(err)
+    }
+}
+
+/// An error returned by a fallible handler. The original error is kept and
+/// can be recovered with `downcast_ref`, e.g. by an `ErrorMapper`.
+#[derive(Clone)]
+pub struct HandlerError {
+    error: Arc<dyn Any + Send + Sync>,
+    message: String,
+    type_name: &'static str,
+}
+
+impl HandlerError {
+    pub fn new<E>(error: E) -> Self
+    where
+        E: fmt::Display + Send + Sync + 'static,
+    {
+        Self {
+            message: error.to_string(),
+            error: Arc::new(error),
+            type_name: type_name::<E>(),
+        }
+    }
+
+    pub fn is<E: Any>(&self) -> bool {
+        self.error.is::<E>()
+    }
+
+    pub fn downcast_ref<E: Any>(&self) -> Option<&E> {
+        self.error.downcast_ref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Debug for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerError")
+            .field("type_name", &self.type_name)
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    CatchAllNotLast { pattern: String },
+    NotStatic { pattern: String },
+    EmptyParamName { pattern: String },
+    DuplicateParamName { pattern: String, name: String },
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::CatchAllNotLast { pattern } => write!(
+                f,
+                "Catch-all segment must be the last segment of a pattern: {pattern}"
+            ),
+            PatternError::NotStatic { pattern } => {
+                write!(f, "Pattern must not contain dynamic segments: {pattern}")
+            }
+            PatternError::EmptyParamName { pattern } => {
+                write!(f, "Parameter name must not be empty: {pattern}")
+            }
+            PatternError::DuplicateParamName { pattern, name } => {
+                write!(f, "Parameter {name} appears more than once: {pattern}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlError {
+    UnknownRoute(String),
+    MissingParam(String),
+    ExtraParam(String),
+    UnnamedWildcard,
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlError::UnknownRoute(name) => write!(f, "No route is named {name}"),
+            UrlError::MissingParam(name) => write!(f, "Missing value for parameter {name}"),
+            UrlError::ExtraParam(name) => write!(f, "Route has no parameter named {name}"),
+            UrlError::UnnamedWildcard => {
+                f.write_str("Cannot build a URL for a pattern with an unnamed wildcard")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
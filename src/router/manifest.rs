@@ -0,0 +1,67 @@
+use serde::Serialize;
+
+use crate::route::RouteInfo;
+
+/// A serializable listing of a router's routes. Routes are sorted by path and
+/// method, and `version` changes whenever the schema does, so the JSON can be
+/// diffed and consumed by other tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteManifest {
+    pub version: u32,
+    pub routes: Vec<RouteInfo>,
+}
+
+impl RouteManifest {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(routes: Vec<RouteInfo>) -> Self {
+        Self {
+            version: Self::VERSION,
+            routes,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use crate::route::method::Method;
+
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let manifest = RouteManifest::new(vec![RouteInfo {
+            path: "/users/:id".to_string(),
+            method: Some(Method::Get),
+            name: Some("user".to_string()),
+            params: vec!["id".to_string()],
+            tags: vec!["accounts".to_string()],
+            metadata: BTreeMap::from([("owner".to_string(), json!("accounts"))]),
+            extensions: Default::default(),
+        }]);
+
+        let value: serde_json::Value = serde_json::from_str(&manifest.to_json().unwrap()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "version": 1,
+                "routes": [{
+                    "path": "/users/:id",
+                    "method": "GET",
+                    "name": "user",
+                    "params": ["id"],
+                    "tags": ["accounts"],
+                    "metadata": { "owner": "accounts" }
+                }]
+            })
+        );
+    }
+}
@@ -1,13 +1,51 @@
-use std::sync::Arc;
+pub mod batch;
+pub mod config;
+pub mod error;
+pub mod manifest;
+pub mod mount;
+pub mod normalize;
+mod table;
 
-use parking_lot::RwLock;
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    time::Duration,
+};
+
+use arc_swap::ArcSwap;
+
+use batch::RouteBatch;
+use config::{ConfigError, HandlerRegistry, RouterConfig};
+use error::{DispatchError, UrlError};
+use manifest::RouteManifest;
+use mount::{Dispatcher, Mount};
+use normalize::{toggle_trailing_slash, NormalizePolicy, TrailingSlash};
+use parking_lot::Mutex;
+use table::RouteTable;
 
 use crate::{
     dependency::container::{
         dashmap::DashmapDependencyContainer, scoped::ScopedDependencyContainer, DependencyContainer,
     },
-    route::{handler::Handler, path::RoutePath, Route},
-    storage::{hashmap::HashMapStorage, RouteStorage},
+    executor::{BoxFuture, CatchUnwind, Timeout},
+    route::{
+        error_mapper::ErrorMapper,
+        handler::{AsyncHandler, Handler},
+        method::Method,
+        middleware::{self, Layers, Middleware},
+        path::{
+            params::PathParams,
+            query::{split_query, QueryParams},
+            RoutePath,
+        },
+        request::Request,
+        Route, RouteHandler, RouteInfo,
+    },
+    storage::{
+        error::RouteConflict, hashmap::HashMapStorage, pattern::PatternStorage,
+        radix::RadixStorage, RouteMatch, RouteStorage,
+    },
 };
 
 pub type RouterContainer<C, UserScope> = Arc<ScopedDependencyContainer<C, UserScope>>;
@@ -19,7 +57,14 @@ where
     P: RoutePath,
     C: DependencyContainer,
 {
-    storage: Arc<RwLock<S>>,
+    table: ArcSwap<RouteTable<S>>,
+    writer: Mutex<()>,
+    mounts: Vec<Mount<O>>,
+    fallback: Option<Arc<dyn Handler<O, C>>>,
+    error_mapper: Option<Arc<dyn ErrorMapper<O>>>,
+    middleware: Layers<O, C>,
+    timeout: Option<Duration>,
+    normalize: NormalizePolicy,
     pub container: C,
     _p: std::marker::PhantomData<P>,
     _o: std::marker::PhantomData<O>,
@@ -33,28 +78,628 @@ where
 {
     pub fn new(storage: S, container: impl Into<C>) -> Self {
         Self {
-            storage: Arc::new(RwLock::new(storage)),
+            table: ArcSwap::from_pointee(RouteTable::new(storage)),
+            writer: Mutex::new(()),
+            mounts: Vec::new(),
+            fallback: None,
+            error_mapper: None,
+            middleware: Vec::new(),
+            timeout: None,
+            normalize: NormalizePolicy::default(),
             container: container.into(),
             _p: std::marker::PhantomData,
             _o: std::marker::PhantomData,
         }
     }
 
-    pub fn add_route(&mut self, path: impl Into<P>, handler: impl Handler<O, C>) {
-        self.storage.write().add_route(path, handler);
+    pub fn with_normalization(mut self, policy: NormalizePolicy) -> Self {
+        self.normalize = policy;
+        self
+    }
+
+    pub fn set_normalization(&mut self, policy: NormalizePolicy) {
+        self.normalize = policy;
+    }
+
+    pub fn normalization(&self) -> &NormalizePolicy {
+        &self.normalize
+    }
+
+    /// Sets the handler used when no route matches. It receives the request
+    /// with the unmatched path; for a mounted router, that is the path below
+    /// the mount prefix.
+    pub fn with_fallback(mut self, handler: impl Handler<O, C>) -> Self {
+        self.set_fallback(handler);
+        self
+    }
+
+    pub fn set_fallback(&mut self, handler: impl Handler<O, C>) {
+        self.fallback = Some(Arc::new(handler));
+    }
+
+    /// Sets the mapper for handler errors not handled by the route's own
+    /// mapper, including those from the fallback and mounted routers.
+    pub fn with_error_mapper(mut self, mapper: impl ErrorMapper<O>) -> Self {
+        self.set_error_mapper(mapper);
+        self
+    }
+
+    pub fn set_error_mapper(&mut self, mapper: impl ErrorMapper<O>) {
+        self.error_mapper = Some(Arc::new(mapper));
+    }
+
+    /// Adds middleware around every dispatch through this router, including
+    /// those handled by the fallback or a mounted router.
+    pub fn with_middleware(mut self, middleware: impl Middleware<O, C>) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    pub fn add_middleware(&mut self, middleware: impl Middleware<O, C>) {
+        self.middleware.push(Arc::new(middleware));
+    }
+
+    /// Fails async dispatch with `DispatchError::TimedOut` when a handler of
+    /// this router has not finished `after` it started, unless its route sets
+    /// its own timeout. Sync handlers always run to completion.
+    pub fn with_timeout(mut self, after: Duration) -> Self {
+        self.set_timeout(after);
+        self
+    }
+
+    pub fn set_timeout(&mut self, after: Duration) {
+        self.timeout = Some(after);
+    }
+
+    pub fn add_route(
+        &self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.batch(|batch| batch.add_route(path, handler))
+    }
+
+    pub fn add_async_route(
+        &self,
+        path: impl Into<P>,
+        handler: impl AsyncHandler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.batch(|batch| batch.add_async_route(path, handler))
+    }
+
+    pub fn replace_route(
+        &self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.batch(|batch| batch.replace_route(path, handler))
+    }
+
+    pub fn remove_route(&self, path: impl Into<P>) -> Option<Route<P, O, C>> {
+        self.update(|table| RouteBatch::new(table).remove_route(path))
+    }
+
+    pub fn insert_route(&self, route: Route<P, O, C>) -> Result<(), RouteConflict> {
+        self.batch(|batch| batch.insert_route(route))
+    }
+
+    /// Applies every change `changes` makes as a single update, so the route
+    /// table is copied and published once however many routes it registers.
+    /// Nothing is published if `changes` returns an error.
+    pub fn batch<R, E>(
+        &self,
+        changes: impl FnOnce(&mut RouteBatch<'_, S, P, O, C>) -> Result<R, E>,
+    ) -> Result<R, E> {
+        self.try_update(|table| changes(&mut RouteBatch::new(table)))
+    }
+
+    /// Registers every route in `config`, resolving handler names through
+    /// `registry`. Nothing is registered unless every route resolves and
+    /// registers without a conflict.
+    pub fn load_config(
+        &self,
+        config: &RouterConfig,
+        registry: &HandlerRegistry<O, C>,
+    ) -> Result<(), ConfigError>
+    where
+        P: From<String>,
+    {
+        let routes = config.resolve(registry)?;
+        self.batch(|batch| {
+            routes
+                .into_iter()
+                .try_for_each(|route| batch.insert_route(route))
+        })
+        .map_err(ConfigError::Conflict)
+    }
+
+    pub fn add_named_route(
+        &self,
+        name: impl Into<String>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.batch(|batch| batch.add_named_route(name, path, handler))
+    }
+
+    /// Builds the URL of the route registered as `name`, looking in mounted
+    /// routers too. Parameter values are percent-encoded; dispatch decodes
+    /// them again.
+    pub fn url_for(&self, name: &str, params: &PathParams) -> Result<String, UrlError> {
+        if let Some(named) = self.table.load().names.get(name) {
+            return named.pattern.build(params);
+        }
+
+        self.mounts
+            .iter()
+            .map(|mount| mount.url_for(name, params))
+            .find(|result| !matches!(result, Err(UrlError::UnknownRoute(_))))
+            .unwrap_or_else(|| Err(UrlError::UnknownRoute(name.to_string())))
+    }
+
+    pub fn add_method_route(
+        &self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.batch(|batch| batch.add_method_route(method, path, handler))
+    }
+
+    pub fn replace_method_route(
+        &self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.batch(|batch| batch.replace_method_route(method, path, handler))
+    }
+
+    pub fn remove_method_route(
+        &self,
+        method: impl Into<Method>,
+        path: impl Into<P>,
+    ) -> Option<Route<P, O, C>> {
+        self.update(|table| RouteBatch::new(table).remove_method_route(method, path))
+    }
+
+    /// Removes the route registered as `name`. Unlike `remove_route`, this
+    /// also reaches guarded routes.
+    pub fn remove_named_route(&self, name: &str) -> Option<Route<P, O, C>> {
+        self.update(|table| RouteBatch::new(table).remove_named_route(name))
+    }
+
+    /// Applies `change` to a copy of the current route table and publishes the
+    /// copy. Writers are serialized; dispatch never waits on them and keeps
+    /// using the snapshot it loaded.
+    fn update<R>(&self, change: impl FnOnce(&mut RouteTable<S>) -> R) -> R {
+        let _writer = self.writer.lock();
+        let mut table = RouteTable::clone(&self.table.load());
+        let result = change(&mut table);
+        self.table.store(Arc::new(table));
+        result
+    }
+
+    /// Like `update`, but leaves the current table in place if `change` fails.
+    fn try_update<R, E>(
+        &self,
+        change: impl FnOnce(&mut RouteTable<S>) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let _writer = self.writer.lock();
+        let mut table = RouteTable::clone(&self.table.load());
+        let result = change(&mut table)?;
+        self.table.store(Arc::new(table));
+        Ok(result)
+    }
+
+    /// Lists every route this router can dispatch to, including those of
+    /// mounted routers with their prefix applied, sorted by path and method.
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        let mut routes: Vec<RouteInfo> = self
+            .table
+            .load()
+            .storage
+            .routes()
+            .map(Route::info)
+            .chain(self.mounts.iter().flat_map(Mount::routes))
+            .collect();
+
+        routes.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+        routes
+    }
+
+    pub fn manifest(&self) -> RouteManifest {
+        RouteManifest::new(self.routes())
+    }
+
+    pub fn mount(&mut self, prefix: &str, router: impl Dispatcher<O> + 'static) {
+        let mount = Mount::new(prefix, router);
+        let position = self
+            .mounts
+            .partition_point(|existing| existing.prefix_len() >= mount.prefix_len());
+        self.mounts.insert(position, mount);
+    }
+
+    /// Matches `request.path` under the router's normalization policy. With a
+    /// lenient trailing-slash policy, a path that only matches with its
+    /// trailing slash toggled either dispatches to that route (`Ignore`) or
+    /// reports where it lives (`Redirect`).
+    fn match_normalized<'a>(
+        &self,
+        storage: &'a S,
+        request: &mut Request,
+    ) -> Result<RouteMatch<'a, P, O, C>, DispatchError>
+    where
+        C: 'static,
+    {
+        let policy = &self.normalize;
+        let method = request.method.as_ref();
+        let accept = |route: &Route<P, O, C>| route.check_guards(&self.container, request);
+        let lookup = |path: &str| {
+            if policy.case_insensitive {
+                storage.find_route_ignore_case(method, path, &accept)
+            } else {
+                storage.find_route(method, path, &accept)
+            }
+        };
+
+        match lookup(&request.path) {
+            Err(err @ DispatchError::NotFound { .. }) => {
+                let alternate = match policy.trailing_slash {
+                    TrailingSlash::Strict => None,
+                    _ => toggle_trailing_slash(&request.path),
+                };
+                let Some(alternate) = alternate else {
+                    return Err(err);
+                };
+
+                let matched = match lookup(&alternate) {
+                    Err(DispatchError::NotFound { .. }) => return Err(err),
+                    result => result?,
+                };
+                if policy.trailing_slash == TrailingSlash::Redirect {
+                    return Err(DispatchError::Redirect {
+                        location: alternate,
+                    });
+                }
+
+                request.path = alternate;
+                Ok(matched)
+            }
+            result => result,
+        }
+    }
+
+    pub fn dispatch(&self, path: impl AsRef<str>) -> Option<O>
+    where
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.try_dispatch(path).ok()
+    }
+
+    pub fn try_dispatch(&self, path: impl AsRef<str>) -> Result<O, DispatchError>
+    where
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.route_request(Self::build_request(None, path))
+    }
+
+    pub fn dispatch_method(&self, method: impl Into<Method>, path: impl AsRef<str>) -> Option<O>
+    where
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.try_dispatch_method(method, path).ok()
+    }
+
+    pub fn try_dispatch_method(
+        &self,
+        method: impl Into<Method>,
+        path: impl AsRef<str>,
+    ) -> Result<O, DispatchError>
+    where
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.route_request(Self::build_request(Some(method.into()), path))
+    }
+
+    pub async fn dispatch_async(&self, path: impl AsRef<str>) -> Option<O>
+    where
+        O: Send + 'static,
+        C: 'static + Clone,
+    {
+        self.try_dispatch_async(path).await.ok()
+    }
+
+    pub async fn try_dispatch_async(&self, path: impl AsRef<str>) -> Result<O, DispatchError>
+    where
+        O: Send + 'static,
+        C: 'static + Clone,
+    {
+        self.route_request_async(Self::build_request(None, path))
+            .await
+    }
+
+    /// Dispatches `path` with `input` as the request body, readable by
+    /// handlers through the `Input<T>` extractor.
+    pub fn dispatch_with<I>(&self, path: impl AsRef<str>, input: I) -> Option<O>
+    where
+        I: Any + Send + Sync,
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.try_dispatch_with(path, input).ok()
+    }
+
+    pub fn try_dispatch_with<I>(&self, path: impl AsRef<str>, input: I) -> Result<O, DispatchError>
+    where
+        I: Any + Send + Sync,
+        O: 'static,
+        C: 'static + Clone,
+    {
+        self.route_request(Self::build_request(None, path).with_body(input))
     }
 
-    fn match_route(&self, path: impl Into<P>) -> Option<Route<P, O, C>> {
-        self.storage.read().match_route(path)
+    fn build_request(method: Option<Method>, path: impl AsRef<str>) -> Request {
+        let (path, query) = split_query(path.as_ref());
+
+        Request::new(path)
+            .with_method(method)
+            .with_query(query.map(QueryParams::parse).unwrap_or_default())
     }
 
-    pub fn dispatch(&self, path: P) -> Option<O>
+    pub fn route_request(&self, mut request: Request) -> Result<O, DispatchError>
     where
         O: 'static,
         C: 'static + Clone,
     {
-        let r = self.match_route(path)?;
-        Some(r.handle(self.container.clone()))
+        if !self.normalize.is_noop() {
+            request.path = self.normalize.normalize(&request.path);
+        }
+
+        let resolved = self.resolve(&mut request);
+        middleware::around(&self.middleware, &self.container, &mut request, |request| {
+            self.route_inner(request, resolved)
+        })
+    }
+
+    fn route_inner(
+        &self,
+        request: &mut Request,
+        resolved: Result<Target<O, C>, DispatchError>,
+    ) -> Result<O, DispatchError>
+    where
+        O: 'static,
+        C: 'static + Clone,
+    {
+        let target = match resolved {
+            Err(err @ DispatchError::NotFound { .. }) => {
+                for mount in &self.mounts {
+                    if let Some(nested) = mount.nest(request) {
+                        match mount.dispatch(nested) {
+                            Err(DispatchError::NotFound { .. }) => continue,
+                            result => return self.map_error(None, result),
+                        }
+                    }
+                }
+                self.fallback(err)?
+            }
+            resolved => resolved?,
+        };
+        middleware::around(&target.middleware, &self.container, request, |request| {
+            let result = catch_panic(&request.path, || {
+                target.handler.try_handle(self.container.clone(), request)
+            });
+            self.map_error(target.error_mapper.as_deref(), result)
+        })
+    }
+
+    /// Like `route_request`, but also dispatches to async handlers, which
+    /// `route_request` reports as `DispatchError::RequiresAsync`. Sync handlers
+    /// still run on the polling thread.
+    pub async fn route_request_async(&self, mut request: Request) -> Result<O, DispatchError>
+    where
+        O: Send + 'static,
+        C: 'static + Clone,
+    {
+        if !self.normalize.is_noop() {
+            request.path = self.normalize.normalize(&request.path);
+        }
+
+        let resolved = self.resolve(&mut request);
+        let (entered, result) =
+            match middleware::enter(&self.middleware, &self.container, &mut request) {
+                Ok(()) => (
+                    self.middleware.len(),
+                    self.route_inner_async(&mut request, resolved).await,
+                ),
+                Err((entered, err)) => (entered, Err(err)),
+            };
+        middleware::exit(
+            &self.middleware[..entered],
+            &self.container,
+            &request,
+            result,
+        )
+    }
+
+    async fn route_inner_async(
+        &self,
+        request: &mut Request,
+        resolved: Result<Target<O, C>, DispatchError>,
+    ) -> Result<O, DispatchError>
+    where
+        O: Send + 'static,
+        C: 'static + Clone,
+    {
+        let target = match resolved {
+            Err(err @ DispatchError::NotFound { .. }) => {
+                for mount in &self.mounts {
+                    if let Some(nested) = mount.nest(request) {
+                        match mount.dispatch_async(nested).await {
+                            Err(DispatchError::NotFound { .. }) => continue,
+                            result => return self.map_error(None, result),
+                        }
+                    }
+                }
+                self.fallback(err)?
+            }
+            resolved => resolved?,
+        };
+        if let Err((entered, err)) = middleware::enter(&target.middleware, &self.container, request)
+        {
+            return middleware::exit(
+                &target.middleware[..entered],
+                &self.container,
+                request,
+                Err(err),
+            );
+        }
+
+        // Middleware need the request after the handler; otherwise hand it over.
+        let handler_request = if self.middleware.is_empty() && target.middleware.is_empty() {
+            std::mem::take(request)
+        } else {
+            request.clone()
+        };
+        let path = handler_request.path.clone();
+        let result = match catch_panic(&path, || {
+            Ok(target
+                .handler
+                .handle_async(self.container.clone(), handler_request))
+        }) {
+            Ok(future) => {
+                let future = CatchUnwind(future);
+                let caught =
+                    match target.timeout {
+                        Some(after) => Timeout::new(future, after).await.ok_or_else(|| {
+                            DispatchError::TimedOut {
+                                path: path.clone(),
+                                after,
+                            }
+                        }),
+                        None => Ok(future.await),
+                    };
+                caught.and_then(|caught| {
+                    caught.unwrap_or_else(|payload| Err(handler_panicked(&path, payload)))
+                })
+            }
+            Err(err) => Err(err),
+        };
+
+        let result = self.map_error(target.error_mapper.as_deref(), result);
+        middleware::exit(&target.middleware, &self.container, request, result)
+    }
+
+    /// Finds the route for `request` in the route table and fills in the
+    /// matched params and extensions, before any middleware runs. Route
+    /// extensions replace any of the same type the caller supplied. Mounted
+    /// routers are only tried if this reports `NotFound`.
+    fn resolve(&self, request: &mut Request) -> Result<Target<O, C>, DispatchError>
+    where
+        C: 'static,
+    {
+        let table = self.table.load();
+        let matched = self.match_normalized(&table.storage, request)?;
+
+        request.params = matched.params;
+        request.params.decode();
+        request.extensions.extend(&matched.route.extensions);
+        Ok(Target {
+            handler: matched.route.handler().clone(),
+            error_mapper: matched.route.error_mapper().cloned(),
+            middleware: matched.route.middleware().clone(),
+            timeout: matched.route.timeout().or(self.timeout),
+        })
+    }
+
+    /// The fallback handler's target, or `not_found` if there is none.
+    fn fallback(&self, not_found: DispatchError) -> Result<Target<O, C>, DispatchError> {
+        let fallback = self.fallback.as_ref().ok_or(not_found)?;
+        Ok(Target {
+            handler: RouteHandler::Sync(fallback.clone()),
+            error_mapper: None,
+            middleware: Vec::new(),
+            timeout: self.timeout,
+        })
+    }
+
+    /// Offers a handler error to the route's mapper, then the router's.
+    fn map_error(
+        &self,
+        route_mapper: Option<&dyn ErrorMapper<O>>,
+        result: Result<O, DispatchError>,
+    ) -> Result<O, DispatchError>
+    where
+        O: 'static,
+    {
+        let Err(DispatchError::Handler(err)) = result else {
+            return result;
+        };
+
+        route_mapper
+            .into_iter()
+            .chain(self.error_mapper.as_deref())
+            .find_map(|mapper| mapper.map_error(&err))
+            .ok_or(DispatchError::Handler(err))
+    }
+}
+
+/// What a request resolved to in the route table.
+struct Target<O, C> {
+    handler: RouteHandler<O, C>,
+    error_mapper: Option<Arc<dyn ErrorMapper<O>>>,
+    middleware: Layers<O, C>,
+    timeout: Option<Duration>,
+}
+
+/// Runs a handler, reporting a panic as `DispatchError::HandlerPanicked`
+/// instead of unwinding into the caller.
+fn catch_panic<O>(
+    path: &str,
+    handler: impl FnOnce() -> Result<O, DispatchError>,
+) -> Result<O, DispatchError> {
+    panic::catch_unwind(AssertUnwindSafe(handler))
+        .unwrap_or_else(|payload| Err(handler_panicked(path, payload)))
+}
+
+fn handler_panicked(path: &str, payload: Box<dyn Any + Send>) -> DispatchError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+
+    DispatchError::HandlerPanicked {
+        path: path.to_string(),
+        message,
+    }
+}
+
+impl<S, P, O, C> Dispatcher<O> for Router<S, P, O, C>
+where
+    S: RouteStorage<P, O, C> + Send + Sync,
+    P: RoutePath + Send + Sync,
+    O: Send + Sync + 'static,
+    C: DependencyContainer + Clone + Send + Sync + 'static,
+{
+    fn dispatch_request(&self, request: Request) -> Result<O, DispatchError> {
+        self.route_request(request)
+    }
+
+    fn dispatch_request_async(&self, request: Request) -> BoxFuture<'_, Result<O, DispatchError>> {
+        Box::pin(self.route_request_async(request))
+    }
+
+    fn routes(&self) -> Vec<RouteInfo> {
+        Router::routes(self)
+    }
+
+    fn url_for(&self, name: &str, params: &PathParams) -> Result<String, UrlError> {
+        Router::url_for(self, name, params)
     }
 }
 
@@ -65,6 +710,20 @@ pub type StandardRouter<O, P = String, C = DashmapDependencyContainer, UserScope
     Arc<ScopedDependencyContainer<C, UserScope>>,
 >;
 
+pub type PatternRouter<O, P = String, C = DashmapDependencyContainer, UserScope = ()> = Router<
+    PatternStorage<P, O, Arc<ScopedDependencyContainer<C, UserScope>>>,
+    P,
+    O,
+    Arc<ScopedDependencyContainer<C, UserScope>>,
+>;
+
+pub type RadixRouter<O, P = String, C = DashmapDependencyContainer, UserScope = ()> = Router<
+    RadixStorage<P, O, Arc<ScopedDependencyContainer<C, UserScope>>>,
+    P,
+    O,
+    Arc<ScopedDependencyContainer<C, UserScope>>,
+>;
+
 // impl<O, P: RoutePath> Default for StandardRouter<O, P> {
 //     fn default() -> Self {
 //         Self::new(
@@ -76,17 +735,1329 @@ pub type StandardRouter<O, P = String, C = DashmapDependencyContainer, UserScope
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use serde::Deserialize;
+
+    use crate::route::{
+        extensions::Tags,
+        extract::{dep::Dep, error::ExtractError, input::Input, path::Path, query::Query},
+        handler::{fallible, with_extractors, with_params, with_path, with_query, with_request},
+        middleware::{after, before},
+        path::pattern::PathPattern,
+    };
+
+    use crate::executor::block_on;
+
+    use super::{
+        error::{HandlerError, PatternError},
+        *,
+    };
 
     #[test]
     fn test_dispatch() {
-        let mut router: StandardRouter<String> = StandardRouter::default();
-        router.add_route("/test", |_| "test".to_string());
+        let router: StandardRouter<String> = StandardRouter::default();
+        router.add_route("/test", |_| "test".to_string()).unwrap();
 
-        let result = router.dispatch("/test".to_string());
+        let result = router.dispatch("/test");
         assert_eq!(result, Some("test".to_string()));
 
-        let not_found = router.dispatch("/notfound".to_string());
+        let not_found = router.dispatch("/notfound");
         assert_eq!(not_found, None);
     }
+
+    #[test]
+    fn test_add_route_conflict() {
+        let router: StandardRouter<String> = StandardRouter::default();
+        router.add_route("/test", |_| "first".to_string()).unwrap();
+
+        assert!(router.add_route("/test", |_| "second".to_string()).is_err());
+        assert_eq!(router.dispatch("/test"), Some("first".to_string()));
+
+        assert!(router
+            .replace_route("/test", |_| "second".to_string())
+            .unwrap()
+            .is_some());
+        assert_eq!(router.dispatch("/test"), Some("second".to_string()));
+    }
+
+    #[test]
+    fn test_dispatch_with_params() {
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_route(
+                "/users/:id",
+                with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(router.dispatch("/users/42"), Some("42".to_string()));
+        assert_eq!(router.dispatch("/users/43"), Some("43".to_string()));
+        assert_eq!(router.dispatch("/users"), None);
+    }
+
+    #[test]
+    fn test_radix_router_dispatch() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .add_route("/static/*rest", |_| "static".to_string())
+            .unwrap();
+        router
+            .add_route(
+                "/users/:id",
+                with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(router.dispatch("/users/42"), Some("42".to_string()));
+        assert_eq!(
+            router.dispatch("/static/css/site.css"),
+            Some("static".to_string())
+        );
+        assert_eq!(router.dispatch("/users"), None);
+    }
+
+    #[test]
+    fn test_try_dispatch_typed_path() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .add_route(
+                "/users/:id",
+                with_path(|_, Path(id): Path<u64>| format!("user {id}")),
+            )
+            .unwrap();
+
+        assert_eq!(router.try_dispatch("/users/42").unwrap(), "user 42");
+        assert!(matches!(
+            router.try_dispatch("/users/abc"),
+            Err(DispatchError::Extraction(_))
+        ));
+        assert!(matches!(
+            router.try_dispatch("/posts/1"),
+            Err(DispatchError::NotFound { path }) if path == "/posts/1"
+        ));
+    }
+
+    #[test]
+    fn test_dispatch_ignores_query() {
+        #[derive(Deserialize)]
+        struct Search {
+            q: String,
+        }
+
+        let router: StandardRouter<String> = StandardRouter::default();
+        router
+            .add_route(
+                "/search",
+                with_query(|_, Query(search): Query<Search>| search.q),
+            )
+            .unwrap();
+
+        assert_eq!(
+            router.try_dispatch("/search?q=hello%20world").unwrap(),
+            "hello world"
+        );
+        assert!(matches!(
+            router.try_dispatch("/search"),
+            Err(DispatchError::Extraction(_))
+        ));
+    }
+
+    #[test]
+    fn test_mount() {
+        let users: RadixRouter<String> = RadixRouter::default();
+        users
+            .add_route(
+                "/:id",
+                with_params(|_, params: &PathParams| format!("user {}", params.get("id").unwrap())),
+            )
+            .unwrap();
+        users.add_route("/", |_| "users".to_string()).unwrap();
+
+        let mut v1: StandardRouter<String> = StandardRouter::default();
+        v1.add_route("/health", |_| "v1 health".to_string())
+            .unwrap();
+        v1.mount("/users", users);
+
+        let mut api: PatternRouter<String> = PatternRouter::default();
+        api.add_route("/v1/status", |_| "status".to_string())
+            .unwrap();
+        api.mount("/v1", v1);
+
+        assert_eq!(api.dispatch("/v1/users/42"), Some("user 42".to_string()));
+        assert_eq!(api.dispatch("/v1/users"), Some("users".to_string()));
+        assert_eq!(api.dispatch("/v1/health"), Some("v1 health".to_string()));
+        assert_eq!(api.dispatch("/v1/status"), Some("status".to_string()));
+        assert!(matches!(
+            api.try_dispatch("/v1/missing"),
+            Err(DispatchError::NotFound { path }) if path == "/v1/missing"
+        ));
+        assert_eq!(api.dispatch("/v10/health"), None);
+    }
+
+    #[test]
+    fn test_mount_uses_own_container() {
+        use crate::dependency::container::scoped::system::SystemScope;
+
+        let read_value = |container: RouterContainer<DashmapDependencyContainer, ()>| {
+            container
+                .resolve::<u32>()
+                .map_or("missing".to_string(), |value| value.to_string())
+        };
+
+        let mut shared: StandardRouter<String> = StandardRouter::default();
+        let isolated: StandardRouter<String> = StandardRouter::default();
+        let mut parent: StandardRouter<String> = StandardRouter::default();
+
+        parent
+            .container
+            .register_with_default_scope(SystemScope::Global, 7u32);
+        shared.container = parent.container.clone();
+
+        shared.add_route("/value", read_value).unwrap();
+        isolated.add_route("/value", read_value).unwrap();
+        parent.mount("/shared", shared);
+        parent.mount("/isolated", isolated);
+
+        assert_eq!(parent.dispatch("/shared/value"), Some("7".to_string()));
+        assert_eq!(
+            parent.dispatch("/isolated/value"),
+            Some("missing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_method_dispatch() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .add_method_route(Method::Get, "/users/:id", |_| "get".to_string())
+            .unwrap();
+        router
+            .add_method_route("POST", "/users/:id", |_| "post".to_string())
+            .unwrap();
+        router
+            .add_method_route("SUBSCRIBE", "/events", |_| "subscribe".to_string())
+            .unwrap();
+
+        assert_eq!(
+            router.dispatch_method(Method::Get, "/users/1"),
+            Some("get".to_string())
+        );
+        assert_eq!(
+            router.dispatch_method("POST", "/users/1"),
+            Some("post".to_string())
+        );
+        assert_eq!(
+            router.dispatch_method("SUBSCRIBE", "/events"),
+            Some("subscribe".to_string())
+        );
+        assert!(matches!(
+            router.try_dispatch_method(Method::Delete, "/users/1"),
+            Err(DispatchError::MethodNotAllowed {
+                method: Some(Method::Delete),
+                path,
+                allowed,
+            }) if path == "/users/1" && allowed == [Method::Get, Method::Post]
+        ));
+        assert!(matches!(
+            router.try_dispatch_method(Method::Get, "/posts/1"),
+            Err(DispatchError::NotFound { .. })
+        ));
+        assert!(matches!(
+            router.try_dispatch("/users/1"),
+            Err(DispatchError::MethodNotAllowed { method: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_method_dispatch_through_mount() {
+        let v1: StandardRouter<String> = StandardRouter::default();
+        v1.add_method_route(Method::Get, "/health", |_| "ok".to_string())
+            .unwrap();
+
+        let mut api: StandardRouter<String> = StandardRouter::default();
+        api.mount("/v1", v1);
+
+        assert_eq!(
+            api.dispatch_method(Method::Get, "/v1/health"),
+            Some("ok".to_string())
+        );
+        assert!(matches!(
+            api.try_dispatch_method(Method::Post, "/v1/health"),
+            Err(DispatchError::MethodNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_url_for() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .add_named_route("user_posts", "/users/:id/posts/:post_id", |_| {
+                "posts".to_string()
+            })
+            .unwrap();
+        router
+            .insert_route(
+                Route::new("/files/*path", |_| "files".to_string())
+                    .with_method(Some(Method::Get))
+                    .with_name("files"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            router.url_for(
+                "user_posts",
+                &PathParams::from_iter([("id", "42"), ("post_id", "7")])
+            ),
+            Ok("/users/42/posts/7".to_string())
+        );
+        assert_eq!(
+            router.url_for("files", &PathParams::from_iter([("path", "a/b.txt")])),
+            Ok("/files/a/b.txt".to_string())
+        );
+        assert_eq!(
+            router.dispatch_method(Method::Get, "/files/a/b.txt"),
+            Some("files".to_string())
+        );
+
+        assert_eq!(
+            router.url_for("user_posts", &PathParams::from_iter([("id", "42")])),
+            Err(UrlError::MissingParam("post_id".to_string()))
+        );
+        assert_eq!(
+            router.url_for("missing", &PathParams::new()),
+            Err(UrlError::UnknownRoute("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_url_for_round_trip() {
+        let users: PatternRouter<String> = PatternRouter::default();
+        users
+            .add_named_route(
+                "user",
+                "/:id",
+                with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+            )
+            .unwrap();
+
+        let mut router: StandardRouter<String> = StandardRouter::default();
+        router.mount("/api/users", users);
+
+        let url = router
+            .url_for("user", &PathParams::from_iter([("id", "a b/100%")]))
+            .unwrap();
+        assert_eq!(url, "/api/users/a%20b%2F100%25");
+        assert_eq!(router.dispatch(&url), Some("a b/100%".to_string()));
+
+        router.set_normalization(NormalizePolicy::new().percent_decode(true));
+        assert_eq!(router.dispatch(&url), Some("a b/100%".to_string()));
+        assert_eq!(
+            router.url_for("missing", &PathParams::new()),
+            Err(UrlError::UnknownRoute("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_replace_keeps_route_name() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .insert_route(
+                Route::new("/users/:id", |_| "old".to_string())
+                    .with_name("user")
+                    .with_metadata("auth", true)
+                    .with_tag("users"),
+            )
+            .unwrap();
+
+        let replaced = router
+            .replace_route("/users/:id", |_| "new".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(replaced.name.as_deref(), Some("user"));
+
+        let params = PathParams::from_iter([("id", "1")]);
+        assert_eq!(router.url_for("user", &params), Ok("/users/1".to_string()));
+        assert_eq!(router.dispatch("/users/1"), Some("new".to_string()));
+
+        let info = router.routes().remove(0);
+        assert_eq!(info.name.as_deref(), Some("user"));
+        assert_eq!(
+            info.metadata.get("auth"),
+            Some(&serde_json::Value::Bool(true))
+        );
+        assert_eq!(info.tags, vec!["users".to_string()]);
+
+        router
+            .batch(|batch| {
+                batch.swap_route(
+                    Route::new("/users/:id", |_| "renamed".to_string()).with_name("member"),
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            router.url_for("user", &params),
+            Err(UrlError::UnknownRoute("user".to_string()))
+        );
+        assert_eq!(
+            router.url_for("member", &params),
+            Ok("/users/1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_route_name() {
+        let router: StandardRouter<String> = StandardRouter::default();
+        router
+            .add_named_route("home", "/", |_| "home".to_string())
+            .unwrap();
+
+        assert_eq!(
+            router.add_named_route("home", "/index", |_| "index".to_string()),
+            Err(RouteConflict::DuplicateName {
+                name: "home".to_string()
+            })
+        );
+        assert_eq!(router.dispatch("/index"), None);
+        assert_eq!(
+            router.url_for("home", &PathParams::new()),
+            Ok("/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trailing_slash_policies() {
+        let mut router: PatternRouter<String> = PatternRouter::default();
+        router.add_route("/ping", |_| "ping".to_string()).unwrap();
+        router.add_route("/dir/", |_| "dir".to_string()).unwrap();
+
+        assert_eq!(router.dispatch("/ping/"), None);
+
+        router.set_normalization(NormalizePolicy::new().trailing_slash(TrailingSlash::Ignore));
+        assert_eq!(router.dispatch("/ping/"), Some("ping".to_string()));
+        assert_eq!(router.dispatch("/dir"), Some("dir".to_string()));
+
+        router.set_normalization(NormalizePolicy::new().trailing_slash(TrailingSlash::Redirect));
+        assert!(matches!(
+            router.try_dispatch("/ping/"),
+            Err(DispatchError::Redirect { location }) if location == "/ping"
+        ));
+        assert!(matches!(
+            router.try_dispatch("/missing/"),
+            Err(DispatchError::NotFound { path }) if path == "/missing/"
+        ));
+    }
+
+    #[test]
+    fn test_params_do_not_match_empty_segments() {
+        type Container = RouterContainer<DashmapDependencyContainer, ()>;
+
+        fn check<S: RouteStorage<String, String, Container>>(
+            mut router: Router<S, String, String, Container>,
+        ) {
+            router.add_route("/users", |_| "users".to_string()).unwrap();
+            router
+                .add_route(
+                    "/users/:id",
+                    with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+                )
+                .unwrap();
+
+            assert_eq!(router.dispatch("/users/7"), Some("7".to_string()));
+            assert_eq!(router.dispatch("/users/"), None);
+
+            router.set_normalization(NormalizePolicy::new().trailing_slash(TrailingSlash::Ignore));
+            assert_eq!(router.dispatch("/users/"), Some("users".to_string()));
+
+            router
+                .set_normalization(NormalizePolicy::new().trailing_slash(TrailingSlash::Redirect));
+            assert!(matches!(
+                router.try_dispatch("/users/"),
+                Err(DispatchError::Redirect { location }) if location == "/users"
+            ));
+        }
+
+        check(PatternRouter::default());
+        check(RadixRouter::default());
+    }
+
+    #[test]
+    fn test_redirect_through_mount() {
+        let api: PatternRouter<String> = PatternRouter::default()
+            .with_normalization(NormalizePolicy::new().trailing_slash(TrailingSlash::Redirect));
+        api.add_route("/users", |_| "users".to_string()).unwrap();
+
+        let mut router: PatternRouter<String> = PatternRouter::default();
+        router.mount("/api", api);
+
+        assert!(matches!(
+            router.try_dispatch("/api/users/"),
+            Err(DispatchError::Redirect { location }) if location == "/api/users"
+        ));
+    }
+
+    #[test]
+    fn test_case_insensitive_keeps_param_case() {
+        let router: RadixRouter<String> = RadixRouter::default()
+            .with_normalization(NormalizePolicy::new().case_insensitive(true));
+        router
+            .add_route(
+                "/users/:name",
+                with_params(|_, params: &PathParams| params.get("name").unwrap().to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(router.dispatch("/USERS/Bob"), Some("Bob".to_string()));
+    }
+
+    #[test]
+    fn test_case_insensitive_mixed_case_routes() {
+        type Container = RouterContainer<DashmapDependencyContainer, ()>;
+
+        fn check<S: RouteStorage<String, String, Container>>(
+            router: Router<S, String, String, Container>,
+        ) {
+            let router = router.with_normalization(NormalizePolicy::new().case_insensitive(true));
+            router.add_route("/Ping", |_| "ping".to_string()).unwrap();
+            router
+                .add_route("/Api/Users", |_| "users".to_string())
+                .unwrap();
+
+            for path in ["/Ping", "/ping", "/PING"] {
+                assert_eq!(router.dispatch(path), Some("ping".to_string()));
+            }
+            for path in ["/Api/Users", "/api/users", "/API/USERS"] {
+                assert_eq!(router.dispatch(path), Some("users".to_string()));
+            }
+            assert!(matches!(
+                router.try_dispatch("/Pong"),
+                Err(DispatchError::NotFound { path }) if path == "/Pong"
+            ));
+        }
+
+        check(StandardRouter::default());
+        check(PatternRouter::default());
+        check(RadixRouter::default());
+    }
+
+    #[test]
+    fn test_lenient_normalization() {
+        let router: StandardRouter<String> =
+            StandardRouter::default().with_normalization(NormalizePolicy::lenient());
+        router
+            .add_route("/users/bob", |_| "bob".to_string())
+            .unwrap();
+
+        for path in ["/users/bob", "//Users/./B%6Fb/", "/users/alice/../bob"] {
+            assert_eq!(router.dispatch(path), Some("bob".to_string()));
+        }
+        assert_eq!(router.dispatch("/users/alice"), None);
+    }
+
+    #[test]
+    fn test_dot_segments_keep_relative_paths() {
+        let router: RadixRouter<String> = RadixRouter::default()
+            .with_normalization(NormalizePolicy::new().resolve_dot_segments(true));
+        router.add_route("ping", |_| "pong".to_string()).unwrap();
+
+        assert_eq!(router.dispatch("ping"), Some("pong".to_string()));
+        assert_eq!(router.dispatch("x/../ping"), Some("pong".to_string()));
+    }
+
+    #[test]
+    fn test_remove_route() {
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_named_route("user", "/users/:id", |_| "user".to_string())
+            .unwrap();
+        router
+            .add_method_route(Method::Get, "/items", |_| "items".to_string())
+            .unwrap();
+
+        assert!(router.remove_route("/users/:id").is_some());
+        assert_eq!(router.dispatch("/users/1"), None);
+        assert_eq!(
+            router.url_for("user", &PathParams::from_iter([("id", "1")])),
+            Err(UrlError::UnknownRoute("user".to_string()))
+        );
+
+        assert!(router.remove_route("/items").is_none());
+        assert!(router.remove_method_route(Method::Get, "/items").is_some());
+        assert_eq!(router.dispatch_method(Method::Get, "/items"), None);
+    }
+
+    #[test]
+    fn test_hot_swap_through_shared_router() {
+        let router: Arc<StandardRouter<String>> = Arc::new(StandardRouter::default());
+        router.add_route("/version", |_| "v1".to_string()).unwrap();
+
+        let shared = router.clone();
+        std::thread::spawn(move || {
+            shared
+                .replace_route("/version", |_| "v2".to_string())
+                .unwrap();
+            shared.add_route("/health", |_| "ok".to_string()).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(router.dispatch("/version"), Some("v2".to_string()));
+        assert_eq!(router.dispatch("/health"), Some("ok".to_string()));
+    }
+
+    #[test]
+    fn test_concurrent_writers_and_readers() {
+        let router: Arc<RadixRouter<String>> = Arc::new(RadixRouter::default());
+        router.add_route("/ping", |_| "pong".to_string()).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|writer| {
+                let router = router.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let path = format!("/w{writer}/{i}");
+                        router
+                            .add_route(path.as_str(), move |_| format!("{writer}:{i}"))
+                            .unwrap();
+                        assert_eq!(router.dispatch("/ping"), Some("pong".to_string()));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        for writer in 0..8 {
+            for i in 0..25 {
+                assert_eq!(
+                    router.dispatch(format!("/w{writer}/{i}")),
+                    Some(format!("{writer}:{i}"))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch_borrowed_path() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .add_route(
+                "/users/:id",
+                with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+            )
+            .unwrap();
+
+        let path = String::from("/users/7");
+        assert_eq!(router.dispatch(&path), Some("7".to_string()));
+        assert_eq!(router.dispatch("/users/8"), Some("8".to_string()));
+        assert_eq!(
+            router.dispatch_method(Method::Get, "/users/9?verbose=1"),
+            Some("9".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fallback() {
+        let router: StandardRouter<String> =
+            StandardRouter::default().with_fallback(with_request(|_, request: &Request| {
+                format!("no route for {}", request.path)
+            }));
+        router.add_route("/ping", |_| "pong".to_string()).unwrap();
+        router
+            .add_method_route(Method::Post, "/items", |_| "created".to_string())
+            .unwrap();
+
+        assert_eq!(router.dispatch("/ping"), Some("pong".to_string()));
+        assert_eq!(
+            router.dispatch("/missing?x=1"),
+            Some("no route for /missing".to_string())
+        );
+        assert!(matches!(
+            router.try_dispatch_method(Method::Get, "/items"),
+            Err(DispatchError::MethodNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fallback_per_mount() {
+        let api: StandardRouter<String> =
+            StandardRouter::default().with_fallback(with_request(|_, request: &Request| {
+                format!("api fallback {}", request.path)
+            }));
+        api.add_route("/users", |_| "users".to_string()).unwrap();
+
+        let mut router: StandardRouter<String> =
+            StandardRouter::default().with_fallback(|_| "root fallback".to_string());
+        router.mount("/api", api);
+
+        assert_eq!(router.dispatch("/api/users"), Some("users".to_string()));
+        assert_eq!(
+            router.dispatch("/api/nope"),
+            Some("api fallback /nope".to_string())
+        );
+        assert_eq!(router.dispatch("/nope"), Some("root fallback".to_string()));
+    }
+
+    #[test]
+    fn test_dispatch_errors() {
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_route("/panic", |_| -> String { panic!("boom") })
+            .unwrap();
+        router
+            .add_route(
+                "/typed/:id",
+                with_path(|_, Path(id): Path<u32>| id.to_string()),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            router.try_dispatch("/panic"),
+            Err(DispatchError::HandlerPanicked { path, message })
+                if path == "/panic" && message == "boom"
+        ));
+        assert!(matches!(
+            router.try_dispatch("/missing"),
+            Err(DispatchError::NotFound { path }) if path == "/missing"
+        ));
+        assert!(matches!(
+            router.try_dispatch("/typed/abc"),
+            Err(DispatchError::Extraction(_))
+        ));
+        assert_eq!(router.try_dispatch("/typed/1").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_missing_dependency_names_type() {
+        let err = DispatchError::missing_dependency::<u64>();
+        assert!(matches!(
+            err,
+            DispatchError::MissingDependency { type_name: "u64" }
+        ));
+        assert_eq!(err.to_string(), "No dependency of type u64 is registered");
+    }
+
+    #[test]
+    fn test_routes() {
+        let users: RadixRouter<String> = RadixRouter::default();
+        users.add_route("/", |_| "list".to_string()).unwrap();
+        users
+            .add_named_route("user", "/:id", |_| "user".to_string())
+            .unwrap();
+
+        let mut router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_method_route(Method::Post, "/login", |_| "login".to_string())
+            .unwrap();
+        router
+            .add_method_route(Method::Get, "/login", |_| "form".to_string())
+            .unwrap();
+        router.mount("/users", users);
+
+        let info = |path: &str, method: Option<Method>, name: Option<&str>| {
+            let params: Vec<String> = PathPattern::parse(path)
+                .unwrap()
+                .param_names()
+                .map(str::to_string)
+                .collect();
+            (path.to_string(), method, name.map(str::to_string), params)
+        };
+        let routes: Vec<_> = router
+            .routes()
+            .into_iter()
+            .map(|route| (route.path, route.method, route.name, route.params))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                info("/login", Some(Method::Get), None),
+                info("/login", Some(Method::Post), None),
+                info("/users", None, None),
+                info("/users/:id", None, Some("user")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest() {
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .insert_route(
+                Route::new("/users/:id", |_| "user".to_string())
+                    .with_method(Some(Method::Get))
+                    .with_name("user")
+                    .with_metadata("auth", "required")
+                    .with_tag("users"),
+            )
+            .unwrap();
+        router.add_route("/health", |_| "ok".to_string()).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&router.manifest().to_json().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "routes": [
+                    {
+                        "path": "/health",
+                        "method": null,
+                        "name": null,
+                        "params": [],
+                        "tags": [],
+                        "metadata": {}
+                    },
+                    {
+                        "path": "/users/:id",
+                        "method": "GET",
+                        "name": "user",
+                        "params": ["id"],
+                        "tags": ["users"],
+                        "metadata": { "auth": "required" }
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_config() {
+        let registry = HandlerRegistry::new()
+            .with("get_user", |_| "user".to_string())
+            .with("health", |_| "ok".to_string());
+        let config = RouterConfig::from_json(
+            r#"{ "routes": [
+                { "path": "/users/:id", "method": "GET", "handler": "get_user", "name": "user" },
+                { "path": "/health", "handler": "health" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let router: PatternRouter<String> = PatternRouter::default();
+        router.load_config(&config, &registry).unwrap();
+
+        assert_eq!(
+            router.dispatch_method(Method::Get, "/users/1"),
+            Some("user".to_string())
+        );
+        assert_eq!(router.dispatch("/health"), Some("ok".to_string()));
+        assert_eq!(
+            router.url_for("user", &PathParams::from_iter([("id", "1")])),
+            Ok("/users/1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_load_config_is_all_or_nothing() {
+        let registry = HandlerRegistry::new().with("health", |_| "ok".to_string());
+        let config = RouterConfig::from_json(
+            r#"{ "routes": [
+                { "path": "/health", "handler": "health" },
+                { "path": "/status", "handler": "health" },
+                { "path": "/health", "handler": "health" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let router: StandardRouter<String> = StandardRouter::default();
+        assert_eq!(
+            router.load_config(&config, &registry),
+            Err(ConfigError::Conflict(RouteConflict::Duplicate {
+                method: None,
+                path: "/health".to_string()
+            }))
+        );
+        assert!(router.routes().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_an_error() {
+        let invalid = PatternError::CatchAllNotLast {
+            pattern: "/a/*rest/b".to_string(),
+        };
+
+        let router: StandardRouter<String> = StandardRouter::default();
+        assert_eq!(
+            router.add_route("/a/*rest/b", |_| "a".to_string()),
+            Err(RouteConflict::InvalidPattern(invalid.clone()))
+        );
+        assert_eq!(
+            router
+                .replace_route("/a/*rest/b", |_| "a".to_string())
+                .err(),
+            Some(RouteConflict::InvalidPattern(invalid.clone()))
+        );
+
+        let registry = HandlerRegistry::new().with("a", |_| "a".to_string());
+        let config = RouterConfig::from_json(
+            r#"{ "routes": [
+                { "path": "/ok", "handler": "a" },
+                { "path": "/a/*rest/b", "handler": "a" }
+            ] }"#,
+        )
+        .unwrap();
+        let router: RadixRouter<String> = RadixRouter::default();
+        assert_eq!(
+            router.load_config(&config, &registry),
+            Err(ConfigError::InvalidPattern(invalid))
+        );
+        assert!(router.routes().is_empty());
+    }
+
+    #[test]
+    fn test_guards() {
+        let router: RadixRouter<String> = RadixRouter::default();
+        router
+            .insert_route(
+                Route::new("/items", |_| "v2".to_string())
+                    .with_guard(|_: &_, request: &Request| request.query.get("v") == Some("2")),
+            )
+            .unwrap();
+        router.add_route("/items", |_| "v1".to_string()).unwrap();
+        router
+            .insert_route(
+                Route::new("/items/special", |_| "special".to_string()).with_guard(
+                    |container: &RouterContainer<DashmapDependencyContainer, ()>, _: &Request| {
+                        container.resolve::<bool>().is_some_and(|enabled| *enabled)
+                    },
+                ),
+            )
+            .unwrap();
+        router
+            .add_route(
+                "/items/:id",
+                with_params(|_, params: &PathParams| format!("id {}", params.get("id").unwrap())),
+            )
+            .unwrap();
+
+        assert_eq!(router.dispatch("/items?v=2"), Some("v2".to_string()));
+        assert_eq!(router.dispatch("/items?v=1"), Some("v1".to_string()));
+        assert_eq!(
+            router.dispatch("/items/special"),
+            Some("id special".to_string())
+        );
+
+        router.container.register(true);
+        assert_eq!(
+            router.dispatch("/items/special"),
+            Some("special".to_string())
+        );
+    }
+
+    #[test]
+    fn test_guarded_route_identity() {
+        type Container = RouterContainer<DashmapDependencyContainer, ()>;
+
+        fn check<S: RouteStorage<String, String, Container>>(
+            router: Router<S, String, String, Container>,
+        ) {
+            let v2 = |output: &'static str| {
+                Route::new("/items", move |_| output.to_string())
+                    .with_name("items_v2")
+                    .with_guard(|_: &_, request: &Request| request.query.get("v") == Some("2"))
+            };
+            router.insert_route(v2("old")).unwrap();
+            router.add_route("/items", |_| "v1".to_string()).unwrap();
+
+            let replaced = router
+                .batch(|batch| batch.swap_route(v2("new")))
+                .unwrap()
+                .unwrap();
+            assert_eq!(replaced.name.as_deref(), Some("items_v2"));
+            assert_eq!(router.routes().len(), 2);
+            assert_eq!(router.dispatch("/items?v=2"), Some("new".to_string()));
+
+            assert!(router.remove_route("/items").is_some());
+            assert_eq!(router.dispatch("/items?v=2"), Some("new".to_string()));
+
+            let removed = router.remove_named_route("items_v2").unwrap();
+            assert!(removed.is_guarded());
+            assert!(router.routes().is_empty());
+            assert_eq!(
+                router.url_for("items_v2", &PathParams::new()),
+                Err(UrlError::UnknownRoute("items_v2".to_string()))
+            );
+            assert!(router.remove_named_route("items_v2").is_none());
+        }
+
+        check(StandardRouter::default());
+        check(PatternRouter::default());
+        check(RadixRouter::default());
+    }
+
+    #[test]
+    fn test_route_extensions_at_dispatch() {
+        struct RateLimit(u32);
+
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .insert_route(
+                Route::new(
+                    "/reports",
+                    with_request(|_, request: &Request| {
+                        let limit = request.extensions.get::<RateLimit>().map_or(0, |l| l.0);
+                        let tags = request
+                            .extensions
+                            .get::<Tags>()
+                            .cloned()
+                            .unwrap_or_default();
+                        format!("{limit} {}", tags.0.join(","))
+                    }),
+                )
+                .with_extension(RateLimit(5))
+                .with_tag("admin")
+                .with_tag("slow"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            router.dispatch("/reports"),
+            Some("5 admin,slow".to_string())
+        );
+        assert_eq!(router.routes()[0].tags, ["admin", "slow"]);
+
+        let mut request = Request::new("/reports");
+        request.extensions.insert(RateLimit(1000));
+        request.extensions.insert(Tags(vec!["public".to_string()]));
+        assert_eq!(router.dispatch_request(request).unwrap(), "5 admin,slow");
+    }
+
+    #[test]
+    fn test_extractor_handler() {
+        fn show(Path(id): Path<u32>, prefix: Dep<String>) -> String {
+            format!("{}{id}", prefix.as_str())
+        }
+
+        let router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_route("/items/:id", with_extractors(show))
+            .unwrap();
+
+        assert!(matches!(
+            router.try_dispatch("/items/1"),
+            Err(DispatchError::MissingDependency { type_name })
+                if type_name == std::any::type_name::<String>()
+        ));
+
+        router.container.register("item-".to_string());
+        assert_eq!(router.dispatch("/items/1"), Some("item-1".to_string()));
+    }
+
+    #[test]
+    fn test_dispatch_with_input() {
+        struct Sum(Vec<i64>);
+
+        let router: PatternRouter<i64> = PatternRouter::default();
+        router
+            .add_route(
+                "/sum",
+                with_extractors(|Input(input): Input<Sum>| input.0.iter().sum()),
+            )
+            .unwrap();
+        router
+            .add_route(
+                "/trace",
+                with_request(|_, request: &Request| {
+                    request.headers.get("x-trace-id").unwrap().parse().unwrap()
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(router.dispatch_with("/sum", Sum(vec![1, 2, 3])), Some(6));
+        assert!(matches!(
+            router.try_dispatch_with("/sum", 6i64),
+            Err(DispatchError::Extraction(ExtractError::InvalidBody { .. }))
+        ));
+        assert_eq!(
+            router
+                .route_request(Request::new("/trace").with_header("X-Trace-Id", "7"))
+                .unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_dispatch_async() {
+        let users: RadixRouter<String> = RadixRouter::default();
+        users
+            .add_async_route(
+                "/:id",
+                with_extractors(|Path(id): Path<u32>| async move { format!("user {id}") }),
+            )
+            .unwrap();
+
+        let mut router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_async_route("/async", |_| async { "async".to_string() })
+            .unwrap();
+        router.add_route("/sync", |_| "sync".to_string()).unwrap();
+        router.mount("/users", users);
+
+        assert_eq!(
+            block_on(router.dispatch_async("/async")),
+            Some("async".to_string())
+        );
+        assert_eq!(
+            block_on(router.dispatch_async("/sync")),
+            Some("sync".to_string())
+        );
+        assert_eq!(
+            block_on(router.dispatch_async("/users/7")),
+            Some("user 7".to_string())
+        );
+        assert!(matches!(
+            block_on(router.try_dispatch_async("/missing")),
+            Err(DispatchError::NotFound { path }) if path == "/missing"
+        ));
+
+        assert!(matches!(
+            router.try_dispatch("/async"),
+            Err(DispatchError::RequiresAsync { path }) if path == "/async"
+        ));
+        assert!(matches!(
+            router.try_dispatch("/users/8"),
+            Err(DispatchError::RequiresAsync { path }) if path == "/8"
+        ));
+        assert_eq!(router.dispatch("/sync"), Some("sync".to_string()));
+    }
+
+    #[test]
+    fn test_async_handler_panic() {
+        let router: StandardRouter<String> = StandardRouter::default();
+        router
+            .add_async_route("/panic", |_| async { panic!("boom") })
+            .unwrap();
+
+        assert!(matches!(
+            block_on(router.try_dispatch_async("/panic")),
+            Err(DispatchError::HandlerPanicked { path, message })
+                if path == "/panic" && message == "boom"
+        ));
+    }
+
+    #[test]
+    fn test_async_timeout() {
+        let after = Duration::from_millis(20);
+        let router: StandardRouter<String> = StandardRouter::default().with_timeout(after);
+        router
+            .add_async_route("/stuck", |_| std::future::pending::<String>())
+            .unwrap();
+        router
+            .add_async_route("/fast", |_| async { "fast".to_string() })
+            .unwrap();
+        router
+            .insert_route(
+                Route::new_async("/patient", |_| async {
+                    std::thread::sleep(Duration::from_millis(40));
+                    "patient".to_string()
+                })
+                .with_timeout(Duration::from_secs(5)),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            block_on(router.try_dispatch_async("/stuck")),
+            Err(DispatchError::TimedOut { path, after: elapsed })
+                if path == "/stuck" && elapsed == after
+        ));
+        assert_eq!(
+            block_on(router.dispatch_async("/fast")),
+            Some("fast".to_string())
+        );
+        assert_eq!(
+            block_on(router.dispatch_async("/patient")),
+            Some("patient".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fallible_handlers() {
+        #[derive(Debug)]
+        struct NotFoundError(u32);
+
+        impl std::fmt::Display for NotFoundError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "no item {}", self.0)
+            }
+        }
+
+        fn item(Path(id): Path<u32>) -> Result<String, NotFoundError> {
+            match id {
+                1 => Ok("item 1".to_string()),
+                id => Err(NotFoundError(id)),
+            }
+        }
+
+        let admin: StandardRouter<String> = StandardRouter::default()
+            .with_error_mapper(|err: &HandlerError| Some(format!("admin: {err}")));
+        admin
+            .add_route(
+                "/fail",
+                fallible(with_extractors(|| Err::<String, _>("denied"))),
+            )
+            .unwrap();
+
+        let mut router: PatternRouter<String> = PatternRouter::default();
+        router
+            .add_route("/items/:id", fallible(with_extractors(item)))
+            .unwrap();
+        router
+            .insert_route(
+                Route::new("/quiet/:id", fallible(with_extractors(item))).with_error_mapper(
+                    |err: &HandlerError| err.is::<NotFoundError>().then(|| "quiet".to_string()),
+                ),
+            )
+            .unwrap();
+        router
+            .add_async_route(
+                "/async",
+                fallible(with_extractors(|| async {
+                    Err::<String, _>("async failure")
+                })),
+            )
+            .unwrap();
+        router.mount("/admin", admin);
+
+        assert_eq!(router.dispatch("/items/1"), Some("item 1".to_string()));
+        let Err(DispatchError::Handler(err)) = router.try_dispatch("/items/2") else {
+            panic!("expected a handler error");
+        };
+        assert_eq!(err.downcast_ref::<NotFoundError>().unwrap().0, 2);
+
+        router.set_error_mapper(|err: &HandlerError| {
+            err.downcast_ref::<NotFoundError>()
+                .map(|err| format!("404 {}", err.0))
+        });
+        assert_eq!(router.dispatch("/items/2"), Some("404 2".to_string()));
+        assert_eq!(router.dispatch("/quiet/2"), Some("quiet".to_string()));
+        assert_eq!(
+            router.dispatch("/admin/fail"),
+            Some("admin: denied".to_string())
+        );
+        assert!(matches!(
+            block_on(router.try_dispatch_async("/async")),
+            Err(DispatchError::Handler(err)) if err.message() == "async failure"
+        ));
+    }
+
+    #[test]
+    fn test_middleware() {
+        struct Timing;
+
+        impl<C> Middleware<String, C> for Timing {
+            fn before(&self, _container: &C, request: &mut Request) -> Result<(), DispatchError> {
+                request.extensions.insert("timed");
+                Ok(())
+            }
+
+            fn after(
+                &self,
+                _container: &C,
+                request: &Request,
+                result: Result<String, DispatchError>,
+            ) -> Result<String, DispatchError> {
+                let label = request.extensions.get::<&str>().unwrap();
+                result.map(|output| format!("{output} ({label})"))
+            }
+        }
+
+        let auth = before(
+            |container: &RouterContainer<DashmapDependencyContainer, ()>, request: &mut Request| {
+                let token = container.resolve::<String>().unwrap();
+                if request.headers.get("authorization") == Some(token.as_str()) {
+                    Ok(())
+                } else {
+                    Err(DispatchError::rejected("bad token"))
+                }
+            },
+        );
+
+        let admin: StandardRouter<String> = StandardRouter::default().with_middleware(auth);
+        admin.container.register("secret".to_string());
+        admin.add_route("/stats", |_| "stats".to_string()).unwrap();
+
+        let mut router: PatternRouter<String> = PatternRouter::default().with_middleware(Timing);
+        router
+            .insert_route(
+                Route::new(
+                    "/users/:id",
+                    with_extractors(|Path(id): Path<u32>| id.to_string()),
+                )
+                .with_middleware(after(|_: &_, request: &Request, result| {
+                    result.map(|id| format!("user {id} at {}", request.path))
+                })),
+            )
+            .unwrap();
+        router
+            .add_async_route("/async", |_| async { "async".to_string() })
+            .unwrap();
+        router.mount("/admin", admin);
+
+        assert_eq!(
+            router.dispatch("/users/7"),
+            Some("user 7 at /users/7 (timed)".to_string())
+        );
+        assert_eq!(
+            block_on(router.dispatch_async("/async")),
+            Some("async (timed)".to_string())
+        );
+        assert!(matches!(
+            router.try_dispatch("/admin/stats"),
+            Err(DispatchError::Rejected { reason }) if reason == "bad token"
+        ));
+        assert_eq!(
+            router
+                .route_request(Request::new("/admin/stats").with_header("Authorization", "secret"))
+                .unwrap(),
+            "stats (timed)"
+        );
+        assert_eq!(
+            block_on(router.route_request_async(
+                Request::new("/admin/stats").with_header("Authorization", "secret")
+            ))
+            .unwrap(),
+            "stats (timed)"
+        );
+        assert!(matches!(
+            router.try_dispatch("/missing"),
+            Err(DispatchError::NotFound { path }) if path == "/missing"
+        ));
+    }
+
+    #[test]
+    fn test_router_middleware_sees_route_tags() {
+        let admin_only = before(|_: &_, request: &mut Request| {
+            let tags = request.extensions.get::<Tags>();
+            if tags.is_some_and(|tags| tags.0.iter().any(|tag| tag == "admin"))
+                && request.headers.get("authorization").is_none()
+            {
+                return Err(DispatchError::rejected("admin only"));
+            }
+            Ok(())
+        });
+
+        let router: RadixRouter<String> = RadixRouter::default()
+            .with_middleware(admin_only)
+            .with_fallback(|_| "fallback".to_string());
+        router
+            .insert_route(Route::new("/stats", |_| "stats".to_string()).with_tag("admin"))
+            .unwrap();
+        router.add_route("/health", |_| "ok".to_string()).unwrap();
+
+        assert!(matches!(
+            router.try_dispatch("/stats"),
+            Err(DispatchError::Rejected { reason }) if reason == "admin only"
+        ));
+        assert!(matches!(
+            block_on(router.try_dispatch_async("/stats")),
+            Err(DispatchError::Rejected { reason }) if reason == "admin only"
+        ));
+        assert_eq!(
+            router
+                .route_request(Request::new("/stats").with_header("Authorization", "x"))
+                .unwrap(),
+            "stats"
+        );
+        assert_eq!(router.dispatch("/health"), Some("ok".to_string()));
+        assert_eq!(router.dispatch("/missing"), Some("fallback".to_string()));
+    }
 }
@@ -0,0 +1,164 @@
+use crate::{
+    executor::BoxFuture,
+    route::{
+        path::{params::PathParams, pattern::split_segments},
+        request::Request,
+        RouteInfo,
+    },
+};
+
+use super::error::{DispatchError, UrlError};
+
+pub trait Dispatcher<O>: Send + Sync {
+    fn dispatch_request(&self, request: Request) -> Result<O, DispatchError>;
+
+    /// Dispatches to async handlers as well as sync ones, which
+    /// `dispatch_request` turns down with `DispatchError::RequiresAsync`. By
+    /// default this runs `dispatch_request` and returns its result as a
+    /// completed future.
+    fn dispatch_request_async(&self, request: Request) -> BoxFuture<'_, Result<O, DispatchError>>
+    where
+        O: Send + 'static,
+    {
+        Box::pin(std::future::ready(self.dispatch_request(request)))
+    }
+
+    fn routes(&self) -> Vec<RouteInfo> {
+        Vec::new()
+    }
+
+    /// Builds the URL of a named route, relative to this dispatcher.
+    fn url_for(&self, name: &str, _params: &PathParams) -> Result<String, UrlError> {
+        Err(UrlError::UnknownRoute(name.to_string()))
+    }
+}
+
+pub(crate) struct Mount<O> {
+    prefix: Vec<String>,
+    dispatcher: Box<dyn Dispatcher<O>>,
+}
+
+impl<O> Mount<O> {
+    pub(crate) fn new(prefix: &str, dispatcher: impl Dispatcher<O> + 'static) -> Self {
+        Self {
+            prefix: split_segments(prefix.trim_end_matches('/'))
+                .map(str::to_string)
+                .collect(),
+            dispatcher: Box::new(dispatcher),
+        }
+    }
+
+    pub(crate) fn prefix_len(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// The request as seen by the mounted dispatcher, if its path is under
+    /// the mount's prefix.
+    pub(crate) fn nest(&self, request: &Request) -> Option<Request> {
+        let path = self.strip_prefix(&request.path)?;
+        let mut nested = request.clone();
+        nested.path = path;
+        Some(nested)
+    }
+
+    pub(crate) fn strip_prefix(&self, path: &str) -> Option<String> {
+        let mut segments = split_segments(path);
+        for expected in &self.prefix {
+            if segments.next()? != expected {
+                return None;
+            }
+        }
+
+        Some(format!("/{}", segments.collect::<Vec<_>>().join("/")))
+    }
+
+    pub(crate) fn routes(&self) -> Vec<RouteInfo> {
+        self.dispatcher
+            .routes()
+            .into_iter()
+            .map(|info| RouteInfo {
+                path: self.prefixed(&info.path),
+                ..info
+            })
+            .collect()
+    }
+
+    pub(crate) fn url_for(&self, name: &str, params: &PathParams) -> Result<String, UrlError> {
+        self.dispatcher
+            .url_for(name, params)
+            .map(|url| self.prefixed(&url))
+    }
+
+    fn prefixed(&self, path: &str) -> String {
+        format!(
+            "/{}",
+            self.prefix
+                .iter()
+                .map(String::as_str)
+                .chain(split_segments(path))
+                .collect::<Vec<_>>()
+                .join("/")
+        )
+    }
+
+    pub(crate) fn dispatch(&self, request: Request) -> Result<O, DispatchError> {
+        self.dispatcher
+            .dispatch_request(request)
+            .map_err(|err| self.prefix_redirect(err))
+    }
+
+    pub(crate) async fn dispatch_async(&self, request: Request) -> Result<O, DispatchError>
+    where
+        O: Send + 'static,
+    {
+        self.dispatcher
+            .dispatch_request_async(request)
+            .await
+            .map_err(|err| self.prefix_redirect(err))
+    }
+
+    fn prefix_redirect(&self, err: DispatchError) -> DispatchError {
+        match err {
+            DispatchError::Redirect { location } => DispatchError::Redirect {
+                location: self
+                    .prefix
+                    .iter()
+                    .map(|segment| format!("/{segment}"))
+                    .chain(std::iter::once(location))
+                    .collect(),
+            },
+            err => err,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl Dispatcher<String> for Echo {
+        fn dispatch_request(&self, request: Request) -> Result<String, DispatchError> {
+            Ok(request.path)
+        }
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let mount = Mount::new("/api/v1/", Echo);
+
+        assert_eq!(mount.prefix_len(), 2);
+        assert_eq!(
+            mount.strip_prefix("/api/v1/users/1").as_deref(),
+            Some("/users/1")
+        );
+        assert_eq!(mount.strip_prefix("/api/v1").as_deref(), Some("/"));
+        assert_eq!(
+            mount.strip_prefix("api/v1/users").as_deref(),
+            Some("/users")
+        );
+        assert_eq!(mount.strip_prefix("/api/v10/users"), None);
+        assert_eq!(mount.strip_prefix("/api"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::route::path::encoding::percent_decode;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    #[default]
+    Strict,
+    Ignore,
+    Redirect,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NormalizePolicy {
+    pub trailing_slash: TrailingSlash,
+    /// Matches static segments ignoring ASCII case. Captured parameters keep
+    /// the case they have in the request path.
+    pub case_insensitive: bool,
+    pub collapse_slashes: bool,
+    pub resolve_dot_segments: bool,
+    pub percent_decode: bool,
+}
+
+impl NormalizePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lenient() -> Self {
+        Self {
+            trailing_slash: TrailingSlash::Ignore,
+            case_insensitive: true,
+            collapse_slashes: true,
+            resolve_dot_segments: true,
+            percent_decode: true,
+        }
+    }
+
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    pub fn collapse_slashes(mut self, enabled: bool) -> Self {
+        self.collapse_slashes = enabled;
+        self
+    }
+
+    pub fn resolve_dot_segments(mut self, enabled: bool) -> Self {
+        self.resolve_dot_segments = enabled;
+        self
+    }
+
+    pub fn percent_decode(mut self, enabled: bool) -> Self {
+        self.percent_decode = enabled;
+        self
+    }
+
+    pub fn is_noop(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Applies every policy that rewrites the path itself. Case folding and
+    /// trailing-slash handling happen at match time, so the path handed to the
+    /// handler keeps its original case.
+    pub fn normalize(&self, path: &str) -> String {
+        let mut segments: Vec<String> = path.split('/').map(str::to_string).collect();
+
+        // `%` and `/` stay encoded so the path still splits into the same
+        // segments and captured parameters can be decoded once more.
+        if self.percent_decode {
+            for segment in &mut segments {
+                *segment = percent_decode(segment)
+                    .replace('%', "%25")
+                    .replace('/', "%2F");
+            }
+        }
+
+        if self.collapse_slashes || self.resolve_dot_segments {
+            let last = segments.len() - 1;
+            segments = segments
+                .into_iter()
+                .enumerate()
+                .filter(|(i, segment)| {
+                    *i == 0 || *i == last || !self.collapse_slashes || !segment.is_empty()
+                })
+                .map(|(_, segment)| segment)
+                .collect();
+        }
+
+        if self.resolve_dot_segments {
+            let trailing = matches!(segments.last().map(String::as_str), Some("." | ".."));
+            let absolute = segments.first().is_some_and(String::is_empty);
+            let mut resolved: Vec<String> = Vec::with_capacity(segments.len());
+            for segment in segments {
+                match segment.as_str() {
+                    "." => {}
+                    ".." => {
+                        if resolved.len() > usize::from(absolute) {
+                            resolved.pop();
+                        }
+                    }
+                    _ => resolved.push(segment),
+                }
+            }
+            if trailing || (absolute && resolved.len() == 1) {
+                resolved.push(String::new());
+            }
+            segments = resolved;
+        }
+
+        segments.join("/")
+    }
+}
+
+pub(crate) fn toggle_trailing_slash(path: &str) -> Option<String> {
+    match path.strip_suffix('/') {
+        Some("") => None,
+        Some(stripped) => Some(stripped.to_string()),
+        None if path.is_empty() => None,
+        None => Some(format!("{path}/")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_noop() {
+        let policy = NormalizePolicy::new();
+        assert!(policy.is_noop());
+        assert_eq!(policy.normalize("//a/./b/../%41/"), "//a/./b/../%41/");
+    }
+
+    #[test]
+    fn test_collapse_slashes() {
+        let policy = NormalizePolicy::new().collapse_slashes(true);
+        assert_eq!(policy.normalize("//a///b/"), "/a/b/");
+        assert_eq!(policy.normalize("/a/b"), "/a/b");
+    }
+
+    #[test]
+    fn test_resolve_dot_segments() {
+        let policy = NormalizePolicy::new().resolve_dot_segments(true);
+        assert_eq!(policy.normalize("/a/./b/../c"), "/a/c");
+        assert_eq!(policy.normalize("/a/b/.."), "/a/");
+        assert_eq!(policy.normalize("/../../a"), "/a");
+        assert_eq!(policy.normalize("/.."), "/");
+    }
+
+    #[test]
+    fn test_resolve_dot_segments_without_leading_slash() {
+        let policy = NormalizePolicy::lenient();
+        assert_eq!(policy.normalize("ping"), "ping");
+        assert_eq!(policy.normalize("a/./b/../c"), "a/c");
+        assert_eq!(policy.normalize("a/b/.."), "a/");
+        assert_eq!(policy.normalize("a/../../b"), "b");
+    }
+
+    #[test]
+    fn test_percent_decode_keeps_encoded_slashes() {
+        let policy = NormalizePolicy::new().percent_decode(true);
+        assert_eq!(policy.normalize("/caf%C3%A9/a%2Fb"), "/café/a%2Fb");
+        assert_eq!(policy.normalize("/100%2541"), "/100%2541");
+    }
+
+    #[test]
+    fn test_lenient() {
+        let policy = NormalizePolicy::lenient();
+        assert_eq!(
+            policy.normalize("//Users/./%42ob//posts/../"),
+            "/Users/Bob/"
+        );
+    }
+
+    #[test]
+    fn test_toggle_trailing_slash() {
+        assert_eq!(toggle_trailing_slash("/ping/").as_deref(), Some("/ping"));
+        assert_eq!(toggle_trailing_slash("/ping").as_deref(), Some("/ping/"));
+        assert_eq!(toggle_trailing_slash("/"), None);
+        assert_eq!(toggle_trailing_slash(""), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use crate::{
+    route::{
+        method::Method,
+        path::{pattern::PathPattern, RoutePath},
+        Route,
+    },
+    storage::{error::RouteConflict, RouteStorage},
+};
+
+/// An immutable snapshot of a router's routes. Dispatch reads whichever
+/// snapshot is current; writers clone it, apply their change and publish the
+/// copy.
+#[derive(Default, Clone)]
+pub(crate) struct RouteTable<S> {
+    pub(crate) storage: S,
+    pub(crate) names: HashMap<String, NamedRoute>,
+}
+
+/// Where a named route is registered.
+#[derive(Clone)]
+pub(crate) struct NamedRoute {
+    pub(crate) method: Option<Method>,
+    pub(crate) path: String,
+    pub(crate) pattern: PathPattern,
+}
+
+impl NamedRoute {
+    fn of<P, O, C>(route: &Route<P, O, C>, pattern: PathPattern) -> Self
+    where
+        P: RoutePath,
+    {
+        Self {
+            method: route.method.clone(),
+            path: route.path.string_repr(),
+            pattern,
+        }
+    }
+}
+
+impl<S> RouteTable<S> {
+    pub(crate) fn new(storage: S) -> Self {
+        Self {
+            storage,
+            names: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn insert<P, O, C>(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict>
+    where
+        S: RouteStorage<P, O, C>,
+        P: RoutePath,
+    {
+        let name = route.name.clone();
+        if let Some(name) = &name {
+            if self.names.contains_key(name) {
+                return Err(RouteConflict::DuplicateName { name: name.clone() });
+            }
+        }
+
+        let named = NamedRoute::of(&route, route.path.pattern()?);
+        self.storage.insert(route)?;
+
+        if let Some(name) = name {
+            self.names.insert(name, named);
+        }
+        Ok(())
+    }
+
+    /// Replaces the route registered for the new route's method and path, or
+    /// the guarded one with the same name. The new route keeps the replaced
+    /// route's name unless it brings its own.
+    pub(crate) fn replace<P, O, C>(
+        &mut self,
+        route: Route<P, O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict>
+    where
+        S: RouteStorage<P, O, C>,
+        P: RoutePath,
+    {
+        let named = NamedRoute::of(&route, route.path.pattern()?);
+        let name = route.name.clone();
+        if let Some(name) = &name {
+            let elsewhere = self.names.get(name).is_some_and(|existing| {
+                existing.method != named.method || existing.pattern != named.pattern
+            });
+            if elsewhere {
+                return Err(RouteConflict::DuplicateName { name: name.clone() });
+            }
+        }
+
+        let replaced = self.storage.replace(route)?;
+        let previous = replaced.as_ref().and_then(|route| route.name.clone());
+        if let (Some(previous), Some(_)) = (&previous, &name) {
+            self.names.remove(previous);
+        }
+        if let Some(name) = name.or(previous) {
+            self.names.insert(name, named);
+        }
+        Ok(replaced)
+    }
+
+    pub(crate) fn remove<P, O, C>(
+        &mut self,
+        method: Option<&Method>,
+        path: &str,
+    ) -> Option<Route<P, O, C>>
+    where
+        S: RouteStorage<P, O, C>,
+        P: RoutePath,
+    {
+        let removed = self.storage.remove(method, path)?;
+        if let Some(name) = &removed.name {
+            self.names.remove(name);
+        }
+        Some(removed)
+    }
+
+    /// Removes the route registered as `name`, guarded or not.
+    pub(crate) fn remove_named<P, O, C>(&mut self, name: &str) -> Option<Route<P, O, C>>
+    where
+        S: RouteStorage<P, O, C>,
+        P: RoutePath,
+    {
+        let named = self.names.get(name)?;
+        let removed = self
+            .storage
+            .remove_named(named.method.as_ref(), &named.path, name)?;
+        self.names.remove(name);
+        Some(removed)
+    }
+}
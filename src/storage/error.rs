@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::{route::method::Method, router::error::PatternError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteConflict {
+    Duplicate {
+        method: Option<Method>,
+        path: String,
+    },
+    Ambiguous {
+        method: Option<Method>,
+        path: String,
+        existing: String,
+    },
+    DuplicateName {
+        name: String,
+    },
+    InvalidPattern(PatternError),
+}
+
+impl RouteConflict {
+    pub fn new(method: Option<Method>, path: String, existing: String) -> Self {
+        if path == existing {
+            RouteConflict::Duplicate { method, path }
+        } else {
+            RouteConflict::Ambiguous {
+                method,
+                path,
+                existing,
+            }
+        }
+    }
+}
+
+struct MethodLabel<'a>(&'a Option<Method>);
+
+impl fmt::Display for MethodLabel<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(method) => write!(f, "{method} "),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for RouteConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteConflict::Duplicate { method, path } => {
+                write!(
+                    f,
+                    "Route is already registered: {}{path}",
+                    MethodLabel(method)
+                )
+            }
+            RouteConflict::Ambiguous {
+                method,
+                path,
+                existing,
+            } => write!(
+                f,
+                "Route {label}{path} is ambiguous with existing route {label}{existing}",
+                label = MethodLabel(method)
+            ),
+            RouteConflict::DuplicateName { name } => {
+                write!(f, "Route name is already registered: {name}")
+            }
+            RouteConflict::InvalidPattern(err) => write!(f, "Invalid route pattern: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RouteConflict {}
+
+impl From<PatternError> for RouteConflict {
+    fn from(err: PatternError) -> Self {
+        RouteConflict::InvalidPattern(err)
+    }
+}
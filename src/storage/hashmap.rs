@@ -2,17 +2,34 @@ use std::collections::HashMap;
 
 use crate::{
     dependency::container::DependencyContainer,
-    route::{handler::Handler, path::RoutePath, Route},
+    route::{method::Method, path::RoutePath, Route},
+    router::error::{DispatchError, PatternError},
 };
 
-use super::RouteStorage;
+use super::{
+    error::RouteConflict, method::MethodRoutes, missed, not_found, RouteMatch, RouteStorage,
+};
 
 pub struct HashMapStorage<P, O, C>
 where
     P: RoutePath,
     C: DependencyContainer,
 {
-    routes: HashMap<String, Route<P, O, C>>,
+    routes: HashMap<String, MethodRoutes<Route<P, O, C>>>,
+    folded: HashMap<String, Vec<String>>,
+}
+
+impl<P, O, C> Clone for HashMapStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn clone(&self) -> Self {
+        HashMapStorage {
+            routes: self.routes.clone(),
+            folded: self.folded.clone(),
+        }
+    }
 }
 
 impl<P, O, C> Default for HashMapStorage<P, O, C>
@@ -33,6 +50,66 @@ where
     pub fn new() -> Self {
         HashMapStorage {
             routes: HashMap::new(),
+            folded: HashMap::new(),
+        }
+    }
+
+    /// Only static paths can be keyed; parameters would be stored literally.
+    fn key(route: &Route<P, O, C>) -> Result<String, RouteConflict> {
+        let pattern = route.path.string_repr();
+        if route.path.pattern()?.is_static() {
+            Ok(pattern)
+        } else {
+            Err(PatternError::NotStatic { pattern }.into())
+        }
+    }
+
+    fn entry(&mut self, key: String) -> &mut MethodRoutes<Route<P, O, C>> {
+        if !self.routes.contains_key(&key) {
+            self.folded
+                .entry(key.to_ascii_lowercase())
+                .or_default()
+                .push(key.clone());
+        }
+        self.routes.entry(key).or_default()
+    }
+
+    fn take(
+        &mut self,
+        path: &str,
+        take: impl FnOnce(&mut MethodRoutes<Route<P, O, C>>) -> Option<Route<P, O, C>>,
+    ) -> Option<Route<P, O, C>> {
+        let routes = self.routes.get_mut(path)?;
+        let removed = take(routes);
+        if routes.is_empty() {
+            self.routes.remove(path);
+            self.forget(path);
+        }
+        removed
+    }
+
+    fn forget(&mut self, key: &str) {
+        let folded = key.to_ascii_lowercase();
+        if let Some(keys) = self.folded.get_mut(&folded) {
+            keys.retain(|k| k != key);
+            if keys.is_empty() {
+                self.folded.remove(&folded);
+            }
+        }
+    }
+
+    fn lookup(
+        &self,
+        key: &str,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        let routes = self.routes.get(key).ok_or_else(|| not_found(path))?;
+
+        match routes.select_where(method, |route| accept(route)) {
+            Ok(route) => Ok(RouteMatch::new(route, Default::default())),
+            Err(miss) => Err(missed(miss, method, path).unwrap_or_else(|| not_found(path))),
         }
     }
 }
@@ -42,15 +119,91 @@ where
     P: RoutePath,
     C: DependencyContainer,
 {
-    fn add_route(&mut self, path: impl Into<P>, handler: impl Handler<O, C>) {
-        let path = path.into();
-        self.routes
-            .insert(path.string_repr(), Route::new(path, handler));
+    fn insert(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict> {
+        let key = Self::key(&route)?;
+        let routes = self.entry(key.clone());
+
+        if route.is_guarded() {
+            routes.push(route.method.clone(), route);
+            return Ok(());
+        }
+
+        if routes.get(route.method.as_ref()).is_some() {
+            return Err(RouteConflict::Duplicate {
+                method: route.method,
+                path: key,
+            });
+        }
+
+        routes.insert(route.method.clone(), route);
+        Ok(())
     }
 
-    fn match_route(&self, path: impl Into<P>) -> Option<Route<P, O, C>> {
-        let path = path.into();
-        self.routes.get(&path.string_repr()).cloned()
+    fn replace(&mut self, route: Route<P, O, C>) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        let routes = self.entry(Self::key(&route)?);
+
+        if route.is_guarded() {
+            let name = route.name.clone();
+            return Ok(
+                routes.push_replacing(route.method.clone(), route, |existing| {
+                    name.is_some() && existing.name == name
+                }),
+            );
+        }
+        Ok(routes.insert_with(route.method.clone(), |replaced| route.inherit(replaced)))
+    }
+
+    fn remove(&mut self, method: Option<&Method>, path: &str) -> Option<Route<P, O, C>> {
+        self.take(path, |routes| routes.remove(method))
+    }
+
+    fn remove_named(
+        &mut self,
+        method: Option<&Method>,
+        path: &str,
+        name: &str,
+    ) -> Option<Route<P, O, C>> {
+        self.take(path, |routes| {
+            routes.remove_where(method, |route| route.name.as_deref() == Some(name))
+        })
+    }
+
+    fn routes<'a>(&'a self) -> impl Iterator<Item = &'a Route<P, O, C>>
+    where
+        P: 'a,
+        O: 'a,
+        C: 'a,
+    {
+        self.routes.values().flat_map(MethodRoutes::values)
+    }
+
+    fn find_route(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.lookup(path, method, path, accept)
+    }
+
+    fn find_route_ignore_case(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        let mut result = self.find_route(method, path, accept);
+        let keys = self.folded.get(&path.to_ascii_lowercase());
+
+        for key in keys.into_iter().flatten().filter(|key| *key != path) {
+            match result {
+                Err(DispatchError::NotFound { .. }) => {
+                    result = self.lookup(key, method, path, accept)
+                }
+                _ => break,
+            }
+        }
+        result
     }
 }
 
@@ -64,7 +217,7 @@ mod tests {
     fn test_add_and_match_route() {
         let mut storage = HashMapStorage::<String, (), DashmapDependencyContainer>::new();
 
-        storage.add_route("/test", |_| ());
+        storage.add_route("/test", |_| ()).unwrap();
 
         let matched = storage.match_route("/test");
         assert!(matched.is_some());
@@ -73,9 +226,125 @@ mod tests {
         assert!(not_matched.is_none());
     }
 
+    #[test]
+    fn test_duplicate_route_conflicts() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+
+        storage.add_route("/test", |_| 1).unwrap();
+        assert_eq!(
+            storage.add_route("/test", |_| 2).err(),
+            Some(RouteConflict::Duplicate {
+                method: None,
+                path: "/test".to_string()
+            })
+        );
+
+        let matched = storage.match_route("/test").unwrap();
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_dynamic_patterns_are_rejected() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+
+        for pattern in ["/users/:id", "/files/*rest", "/a/*/b"] {
+            let not_static = Some(RouteConflict::InvalidPattern(PatternError::NotStatic {
+                pattern: pattern.to_string(),
+            }));
+            assert_eq!(storage.add_route(pattern, |_| 1).err(), not_static);
+            assert_eq!(storage.replace_route(pattern, |_| 1).err(), not_static);
+        }
+        assert_eq!(storage.routes().count(), 0);
+    }
+
+    #[test]
+    fn test_replace_route() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+
+        assert!(storage.replace_route("/test", |_| 1).unwrap().is_none());
+        let replaced = storage.replace_route("/test", |_| 2).unwrap().unwrap();
+        assert_eq!(
+            replaced
+                .try_handle(DashmapDependencyContainer::default(), &Default::default())
+                .unwrap(),
+            1
+        );
+
+        let matched = storage.match_route("/test").unwrap();
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_method_routes() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+
+        storage
+            .add_method_route(Some(Method::Get), "/test", |_| 1)
+            .unwrap();
+        storage
+            .add_method_route(Some(Method::Post), "/test", |_| 2)
+            .unwrap();
+        assert!(storage
+            .add_method_route(Some(Method::Get), "/test", |_| 3)
+            .is_err());
+
+        let handle = |method: Method| {
+            storage
+                .match_method_route(Some(&method), "/test")
+                .and_then(|matched| matched.handle(DashmapDependencyContainer::default()))
+        };
+
+        assert_eq!(handle(Method::Get).unwrap(), 1);
+        assert_eq!(handle(Method::Post).unwrap(), 2);
+        assert!(matches!(
+            handle(Method::Delete),
+            Err(DispatchError::MethodNotAllowed {
+                method: Some(Method::Delete),
+                path,
+                allowed,
+            }) if path == "/test" && allowed == [Method::Get, Method::Post]
+        ));
+        assert!(matches!(
+            storage.match_method_route(Some(&Method::Get), "/missing"),
+            Err(DispatchError::NotFound { .. })
+        ));
+    }
+
     #[test]
     fn test_default() {
         let storage = HashMapStorage::<String, (), DashmapDependencyContainer>::default();
         assert!(storage.routes.is_empty());
     }
+
+    #[test]
+    fn test_remove_route() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+        storage.add_route("/test", |_| 1).unwrap();
+
+        assert!(storage.remove(Some(&Method::Get), "/test").is_none());
+        assert!(storage.remove_route("/test").is_some());
+        assert!(storage.match_route("/test").is_none());
+        assert!(storage.routes.is_empty());
+    }
+
+    #[test]
+    fn test_match_borrows_route() {
+        let mut storage = HashMapStorage::<String, u8, DashmapDependencyContainer>::new();
+        storage.add_route("/test", |_| 1).unwrap();
+
+        let first = storage.match_route("/test").unwrap();
+        let second = storage.match_route("/test").unwrap();
+        assert!(std::ptr::eq(first.route, second.route));
+        assert!(first.params.is_empty());
+    }
 }
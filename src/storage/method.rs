@@ -0,0 +1,322 @@
+use std::collections::BTreeMap;
+
+use crate::route::method::Method;
+
+/// Why `MethodRoutes::select_where` found nothing.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Miss {
+    /// Nothing is registered for the method; these methods are.
+    NotAllowed(Vec<Method>),
+    /// Routes are registered for the method, but none was accepted.
+    Rejected,
+}
+
+/// The routes registered for one method: guarded routes, tried in
+/// registration order, followed by at most one unguarded route.
+#[derive(Clone)]
+struct Candidates<T> {
+    guarded: Vec<T>,
+    default: Option<T>,
+}
+
+impl<T> Candidates<T> {
+    fn new() -> Self {
+        Self {
+            guarded: Vec::new(),
+            default: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.guarded.is_empty() && self.default.is_none()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        self.guarded.iter().chain(&self.default)
+    }
+}
+
+#[derive(Clone)]
+pub struct MethodRoutes<T> {
+    routes: BTreeMap<Option<Method>, Candidates<T>>,
+}
+
+impl<T> Default for MethodRoutes<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MethodRoutes<T> {
+    pub fn new() -> Self {
+        Self {
+            routes: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    fn candidates(&self, method: Option<&Method>) -> Option<&Candidates<T>> {
+        self.routes
+            .iter()
+            .find(|(key, _)| key.as_ref() == method)
+            .map(|(_, candidates)| candidates)
+    }
+
+    /// The unguarded route registered for exactly `method`.
+    pub fn get(&self, method: Option<&Method>) -> Option<&T> {
+        self.candidates(method)?.default.as_ref()
+    }
+
+    /// Picks the first route for `method` that `accept` approves, trying
+    /// routes registered for that method before routes registered without
+    /// one.
+    pub fn select_where(
+        &self,
+        method: Option<&Method>,
+        accept: impl Fn(&T) -> bool,
+    ) -> Result<&T, Miss> {
+        let exact = method.and_then(|method| self.candidates(Some(method)));
+        let any = self.candidates(None);
+
+        if exact.is_none() && any.is_none() {
+            return Err(Miss::NotAllowed(self.allowed()));
+        }
+
+        exact
+            .into_iter()
+            .chain(any)
+            .flat_map(Candidates::iter)
+            .find(|candidate| accept(candidate))
+            .ok_or(Miss::Rejected)
+    }
+
+    pub fn allowed(&self) -> Vec<Method> {
+        self.routes.keys().flatten().cloned().collect()
+    }
+
+    /// Sets the unguarded route for `method`, returning the one it replaces.
+    pub fn insert(&mut self, method: Option<Method>, value: T) -> Option<T> {
+        self.insert_with(method, |_| value)
+    }
+
+    /// Like `insert`, but builds the new route from the one it replaces.
+    pub fn insert_with(
+        &mut self,
+        method: Option<Method>,
+        value: impl FnOnce(Option<&T>) -> T,
+    ) -> Option<T> {
+        let slot = &mut self
+            .routes
+            .entry(method)
+            .or_insert_with(Candidates::new)
+            .default;
+        let value = value(slot.as_ref());
+        slot.replace(value)
+    }
+
+    /// Adds a guarded route for `method` after any registered before it.
+    pub fn push(&mut self, method: Option<Method>, value: T) {
+        self.routes
+            .entry(method)
+            .or_insert_with(Candidates::new)
+            .guarded
+            .push(value);
+    }
+
+    /// Adds a guarded route for `method`, in place of the first guarded
+    /// route that `same` picks out if there is one.
+    pub fn push_replacing(
+        &mut self,
+        method: Option<Method>,
+        value: T,
+        same: impl Fn(&T) -> bool,
+    ) -> Option<T> {
+        let guarded = &mut self
+            .routes
+            .entry(method)
+            .or_insert_with(Candidates::new)
+            .guarded;
+        match guarded.iter_mut().find(|existing| same(existing)) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                guarded.push(value);
+                None
+            }
+        }
+    }
+
+    /// Removes the unguarded route for `method`.
+    pub fn remove(&mut self, method: Option<&Method>) -> Option<T> {
+        self.take(method, |candidates| candidates.default.take())
+    }
+
+    /// Removes the first route for `method`, guarded or not, that `which`
+    /// picks out.
+    pub fn remove_where(
+        &mut self,
+        method: Option<&Method>,
+        which: impl Fn(&T) -> bool,
+    ) -> Option<T> {
+        self.take(method, |candidates| {
+            match candidates.guarded.iter().position(&which) {
+                Some(index) => Some(candidates.guarded.remove(index)),
+                None => candidates.default.take_if(|default| which(default)),
+            }
+        })
+    }
+
+    fn take(
+        &mut self,
+        method: Option<&Method>,
+        take: impl FnOnce(&mut Candidates<T>) -> Option<T>,
+    ) -> Option<T> {
+        let key = self
+            .routes
+            .keys()
+            .find(|key| key.as_ref() == method)?
+            .clone();
+        let candidates = self.routes.get_mut(&key)?;
+        let removed = take(candidates);
+        if candidates.is_empty() {
+            self.routes.remove(&key);
+        }
+        removed
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.routes.values().flat_map(Candidates::iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_by_method() {
+        let mut routes = MethodRoutes::new();
+        routes.insert(Some(Method::Get), "get");
+        routes.insert(Some(Method::Post), "post");
+
+        assert_eq!(
+            routes.select_where(Some(&Method::Get), |_| true),
+            Ok(&"get")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Post), |_| true),
+            Ok(&"post")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Delete), |_| true),
+            Err(Miss::NotAllowed(vec![Method::Get, Method::Post]))
+        );
+        assert_eq!(
+            routes.select_where(None, |_| true),
+            Err(Miss::NotAllowed(vec![Method::Get, Method::Post]))
+        );
+    }
+
+    #[test]
+    fn test_any_method_is_fallback() {
+        let mut routes = MethodRoutes::new();
+        routes.insert(None, "any");
+        routes.insert(Some(Method::Post), "post");
+
+        assert_eq!(
+            routes.select_where(Some(&Method::Post), |_| true),
+            Ok(&"post")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Get), |_| true),
+            Ok(&"any")
+        );
+        assert_eq!(routes.select_where(None, |_| true), Ok(&"any"));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut routes = MethodRoutes::new();
+        routes.insert(None, "any");
+        routes.insert(Some(Method::Get), "get");
+
+        assert_eq!(routes.remove(Some(&Method::Post)), None);
+        assert_eq!(routes.remove(Some(&Method::Get)), Some("get"));
+        assert_eq!(routes.remove(None), Some("any"));
+        assert!(routes.is_empty());
+    }
+
+    #[test]
+    fn test_select_where() {
+        let mut routes = MethodRoutes::new();
+        routes.push(Some(Method::Get), "v2");
+        routes.insert(Some(Method::Get), "get");
+        routes.push(None, "any-v2");
+
+        assert_eq!(
+            routes.select_where(Some(&Method::Get), |route| route.ends_with("v2")),
+            Ok(&"v2")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Get), |route| !route.ends_with("v2")),
+            Ok(&"get")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Post), |_| true),
+            Ok(&"any-v2")
+        );
+        assert_eq!(
+            routes.select_where(Some(&Method::Post), |_| false),
+            Err(Miss::Rejected)
+        );
+        assert_eq!(routes.get(Some(&Method::Get)), Some(&"get"));
+        assert_eq!(routes.values().count(), 3);
+    }
+
+    #[test]
+    fn test_not_allowed() {
+        let mut routes = MethodRoutes::new();
+        routes.push(Some(Method::Get), "v2");
+
+        assert_eq!(
+            routes.select_where(Some(&Method::Post), |_| true),
+            Err(Miss::NotAllowed(vec![Method::Get]))
+        );
+        assert_eq!(routes.remove(Some(&Method::Get)), None);
+        assert!(!routes.is_empty());
+    }
+
+    #[test]
+    fn test_guarded_identity() {
+        let mut routes = MethodRoutes::new();
+        routes.push(Some(Method::Get), "v1");
+        routes.insert(Some(Method::Get), "get");
+
+        assert_eq!(
+            routes.push_replacing(Some(Method::Get), "v2", |route| route.starts_with('v')),
+            Some("v1")
+        );
+        assert_eq!(
+            routes.push_replacing(Some(Method::Get), "beta", |route| route.starts_with('b')),
+            None
+        );
+        assert_eq!(routes.select_where(Some(&Method::Get), |_| true), Ok(&"v2"));
+
+        assert_eq!(
+            routes.remove_where(Some(&Method::Get), |route| *route == "beta"),
+            Some("beta")
+        );
+        assert_eq!(
+            routes.remove_where(Some(&Method::Get), |route| *route == "get"),
+            Some("get")
+        );
+        assert_eq!(routes.remove_where(Some(&Method::Get), |_| false), None);
+        assert_eq!(
+            routes.remove_where(Some(&Method::Get), |route| *route == "v2"),
+            Some("v2")
+        );
+        assert!(routes.is_empty());
+    }
+}
@@ -1,12 +1,196 @@
-use crate::route::{handler::Handler, path::RoutePath, Route};
+use crate::{
+    route::{
+        handler::Handler,
+        method::Method,
+        path::{params::PathParams, RoutePath},
+        request::Request,
+        Route,
+    },
+    router::error::DispatchError,
+};
 
+use error::RouteConflict;
+use method::Miss;
+
+pub mod error;
 pub mod hashmap;
+pub mod method;
+pub mod pattern;
+pub mod radix;
+
+/// A route found by a lookup. It borrows the route from the storage, so a
+/// lookup that captures no parameters does not allocate.
+pub struct RouteMatch<'a, P, O, C>
+where
+    P: RoutePath,
+{
+    pub route: &'a Route<P, O, C>,
+    pub params: PathParams,
+}
 
-pub trait RouteStorage<P, O, C>
+impl<'a, P, O, C> RouteMatch<'a, P, O, C>
 where
     P: RoutePath,
 {
-    fn add_route(&mut self, path: impl Into<P>, handler: impl Handler<O, C>);
+    pub fn new(route: &'a Route<P, O, C>, params: PathParams) -> Self {
+        Self { route, params }
+    }
+
+    pub fn handle(&self, container: C) -> Result<O, DispatchError>
+    where
+        C: 'static,
+        O: 'static,
+    {
+        let mut request = Request::default().with_params(self.params.clone());
+        request.extensions = self.route.extensions.clone();
+        self.route.try_handle(container, &request)
+    }
+}
+
+/// Storage for a router's route table.
+///
+/// Implementations that understand patterns resolve overlapping routes by
+/// segment, left to right: a static segment beats a `:param` or `*` wildcard,
+/// which in turn beats a `*rest` catch-all. `/users/me` therefore wins over
+/// `/users/:id`, which wins over `/users/*rest`.
+///
+/// When several handlers share a path, the one registered for the request's
+/// method is used, falling back to a handler registered without a method. The
+/// most specific matching path decides: if it has no handler for the method,
+/// matching fails with `DispatchError::MethodNotAllowed` instead of trying a
+/// less specific pattern.
+///
+/// Guarded routes are tried before the unguarded route for the same method
+/// and path. If every route at the most specific path is turned down by its
+/// guards, matching carries on with the next matching path.
+pub trait RouteStorage<P, O, C>: Clone
+where
+    P: RoutePath,
+{
+    /// Registers `route`, refusing one that would shadow an existing route for
+    /// the same method: same path, or patterns that only differ in parameter
+    /// names (`/a/:x` and `/a/:y`). Guarded routes are always added. Fails
+    /// with `RouteConflict::InvalidPattern` if the path is not a valid pattern.
+    fn insert(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict>;
+
+    /// Registers `route` over whatever it would shadow and returns the route
+    /// it displaced. A guarded route displaces the guarded one with the same
+    /// name, method and path; an unnamed guarded route is only ever added.
+    /// Invalid patterns are rejected as by `insert`.
+    fn replace(&mut self, route: Route<P, O, C>) -> Result<Option<Route<P, O, C>>, RouteConflict>;
+
+    /// Removes the unguarded route with exactly this method and path, as it
+    /// was written at registration.
+    fn remove(&mut self, method: Option<&Method>, path: &str) -> Option<Route<P, O, C>>;
+
+    /// Removes the route called `name` registered for `method` and `path`,
+    /// guarded or not.
+    fn remove_named(
+        &mut self,
+        method: Option<&Method>,
+        path: &str,
+        name: &str,
+    ) -> Option<Route<P, O, C>>;
+
+    /// Every registered route, in no particular order.
+    fn routes<'a>(&'a self) -> impl Iterator<Item = &'a Route<P, O, C>>
+    where
+        P: 'a,
+        O: 'a,
+        C: 'a;
+
+    /// Finds the route for `method` and `path`, skipping routes that `accept`
+    /// turns down as if they were not registered.
+    fn find_route(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError>;
+
+    /// Like `find_route`, but compares static segments ignoring ASCII case.
+    /// A route whose case matches exactly is preferred.
+    fn find_route_ignore_case(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError>;
+
+    fn match_method_route(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.find_route(method, path, &|_| true)
+    }
+
+    fn add_method_route(
+        &mut self,
+        method: Option<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.insert(Route::new(path, handler).with_method(method))
+    }
+
+    fn replace_method_route(
+        &mut self,
+        method: Option<Method>,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.replace(Route::new(path, handler).with_method(method))
+    }
+
+    fn add_route(
+        &mut self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<(), RouteConflict> {
+        self.add_method_route(None, path, handler)
+    }
+
+    fn replace_route(
+        &mut self,
+        path: impl Into<P>,
+        handler: impl Handler<O, C>,
+    ) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        self.replace_method_route(None, path, handler)
+    }
+
+    fn remove_route(&mut self, path: &str) -> Option<Route<P, O, C>> {
+        self.remove(None, path)
+    }
+
+    fn match_route(&self, path: &str) -> Option<RouteMatch<'_, P, O, C>> {
+        self.match_method_route(None, path).ok()
+    }
+}
+
+pub(crate) fn method_not_allowed(
+    method: Option<&Method>,
+    path: &str,
+    allowed: Vec<Method>,
+) -> DispatchError {
+    DispatchError::MethodNotAllowed {
+        method: method.cloned(),
+        path: path.to_string(),
+        allowed,
+    }
+}
+
+/// Turns a miss at a matching path into the error that ends the lookup, or
+/// `None` if the lookup should go on to the next candidate path.
+pub(crate) fn missed(miss: Miss, method: Option<&Method>, path: &str) -> Option<DispatchError> {
+    match miss {
+        Miss::NotAllowed(allowed) => Some(method_not_allowed(method, path, allowed)),
+        Miss::Rejected => None,
+    }
+}
 
-    fn match_route(&self, path: impl Into<P>) -> Option<Route<P, O, C>>;
+pub(crate) fn not_found(path: &str) -> DispatchError {
+    DispatchError::NotFound {
+        path: path.to_string(),
+    }
 }
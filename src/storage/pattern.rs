@@ -0,0 +1,450 @@
+use crate::{
+    dependency::container::DependencyContainer,
+    route::{
+        method::Method,
+        path::{params::PathParams, pattern::PathPattern, RoutePath},
+        Route,
+    },
+    router::error::DispatchError,
+};
+
+use super::{
+    error::RouteConflict, method::MethodRoutes, missed, not_found, RouteMatch, RouteStorage,
+};
+
+type Leaf<P, O, C> = (PathPattern, Route<P, O, C>);
+type Entry<P, O, C> = (PathPattern, MethodRoutes<Leaf<P, O, C>>);
+
+pub struct PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    routes: Vec<Entry<P, O, C>>,
+}
+
+impl<P, O, C> Clone for PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn clone(&self) -> Self {
+        PatternStorage {
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl<P, O, C> Default for PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, O, C> PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    pub fn new() -> Self {
+        PatternStorage { routes: Vec::new() }
+    }
+
+    fn entry(&mut self, pattern: &PathPattern) -> &mut MethodRoutes<Leaf<P, O, C>> {
+        let index = match self
+            .routes
+            .iter()
+            .position(|(existing, _)| existing.is_ambiguous_with(pattern))
+        {
+            Some(index) => index,
+            None => {
+                let specificity = pattern.specificity();
+                let index = self
+                    .routes
+                    .partition_point(|(existing, _)| existing.specificity() <= specificity);
+                self.routes
+                    .insert(index, (pattern.clone(), MethodRoutes::new()));
+                index
+            }
+        };
+
+        &mut self.routes[index].1
+    }
+}
+
+impl<P, O, C> RouteStorage<P, O, C> for PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn insert(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict> {
+        let pattern = route.path.pattern()?;
+        let routes = self.entry(&pattern);
+
+        if route.is_guarded() {
+            routes.push(route.method.clone(), (pattern, route));
+            return Ok(());
+        }
+
+        if let Some((_, existing)) = routes.get(route.method.as_ref()) {
+            return Err(RouteConflict::new(
+                route.method,
+                route.path.string_repr(),
+                existing.path.string_repr(),
+            ));
+        }
+
+        routes.insert(route.method.clone(), (pattern, route));
+        Ok(())
+    }
+
+    fn replace(&mut self, route: Route<P, O, C>) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        let pattern = route.path.pattern()?;
+        let routes = self.entry(&pattern);
+
+        if route.is_guarded() {
+            let name = route.name.clone();
+            return Ok(routes
+                .push_replacing(route.method.clone(), (pattern, route), |(_, existing)| {
+                    name.is_some() && existing.name == name
+                })
+                .map(|(_, route)| route));
+        }
+        Ok(routes
+            .insert_with(route.method.clone(), |replaced| {
+                (pattern, route.inherit(replaced.map(|(_, route)| route)))
+            })
+            .map(|(_, route)| route))
+    }
+
+    fn remove(&mut self, method: Option<&Method>, path: &str) -> Option<Route<P, O, C>> {
+        self.take(path, |pattern, routes| {
+            if routes.get(method)?.0 != *pattern {
+                return None;
+            }
+            routes.remove(method)
+        })
+    }
+
+    fn remove_named(
+        &mut self,
+        method: Option<&Method>,
+        path: &str,
+        name: &str,
+    ) -> Option<Route<P, O, C>> {
+        self.take(path, |pattern, routes| {
+            routes.remove_where(method, |(existing, route)| {
+                existing == pattern && route.name.as_deref() == Some(name)
+            })
+        })
+    }
+
+    fn routes<'a>(&'a self) -> impl Iterator<Item = &'a Route<P, O, C>>
+    where
+        P: 'a,
+        O: 'a,
+        C: 'a,
+    {
+        self.routes
+            .iter()
+            .flat_map(|(_, routes)| routes.values().map(|(_, route)| route))
+    }
+
+    fn find_route(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.lookup(method, path, accept, PathPattern::matches)
+    }
+
+    fn find_route_ignore_case(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.find_route(method, path, accept)
+            .or_else(|err| match err {
+                DispatchError::NotFound { .. } => {
+                    self.lookup(method, path, accept, PathPattern::matches_ignore_case)
+                }
+                err => Err(err),
+            })
+    }
+}
+
+impl<P, O, C> PatternStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn take(
+        &mut self,
+        path: &str,
+        take: impl FnOnce(&PathPattern, &mut MethodRoutes<Leaf<P, O, C>>) -> Option<Leaf<P, O, C>>,
+    ) -> Option<Route<P, O, C>> {
+        let pattern = PathPattern::parse(path).ok()?;
+        let index = self
+            .routes
+            .iter()
+            .position(|(existing, _)| existing.is_ambiguous_with(&pattern))?;
+        let routes = &mut self.routes[index].1;
+
+        let (_, removed) = take(&pattern, routes)?;
+        if routes.is_empty() {
+            self.routes.remove(index);
+        }
+        Some(removed)
+    }
+
+    fn lookup(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+        matches: fn(&PathPattern, &str) -> Option<PathParams>,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        let candidates = self
+            .routes
+            .iter()
+            .filter(|(pattern, routes)| !routes.is_empty() && matches(pattern, path).is_some());
+
+        for (_, routes) in candidates {
+            match routes.select_where(method, |(_, route)| accept(route)) {
+                Ok((pattern, route)) => {
+                    let params = matches(pattern, path).unwrap_or_default();
+                    return Ok(RouteMatch::new(route, params));
+                }
+                Err(miss) => {
+                    if let Some(err) = missed(miss, method, path) {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Err(not_found(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dependency::container::dashmap::DashmapDependencyContainer,
+        route::{handler::with_params, path::params::PathParams},
+    };
+
+    use super::*;
+
+    type TestStorage = PatternStorage<String, String, DashmapDependencyContainer>;
+
+    #[test]
+    fn test_add_and_match_static_route() {
+        let mut storage = TestStorage::new();
+        storage.add_route("/test", |_| "test".to_string()).unwrap();
+
+        assert!(storage.match_route("/test").is_some());
+        assert!(storage.match_route("/nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_match_captures_params() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route(
+                "/users/:id/posts/:post_id",
+                with_params(|_, params: &PathParams| {
+                    format!(
+                        "{}:{}",
+                        params.get("id").unwrap(),
+                        params.get("post_id").unwrap()
+                    )
+                }),
+            )
+            .unwrap();
+
+        let matched = storage.match_route("/users/42/posts/7").unwrap();
+        assert_eq!(matched.params.get("id"), Some("42"));
+        assert_eq!(matched.params.get("post_id"), Some("7"));
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            "42:7"
+        );
+
+        let matched = storage.match_route("/users/43/posts/8").unwrap();
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            "43:8"
+        );
+
+        assert!(storage.match_route("/users/42").is_none());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/files/*rest", |_| "catch-all".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/:name", |_| "param".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/index", |_| "static".to_string())
+            .unwrap();
+
+        let dispatch = |path: &str| {
+            storage
+                .match_route(path)
+                .and_then(|matched| matched.handle(DashmapDependencyContainer::default()).ok())
+        };
+
+        assert_eq!(dispatch("/files/index").as_deref(), Some("static"));
+        assert_eq!(dispatch("/files/readme").as_deref(), Some("param"));
+        assert_eq!(dispatch("/files/a/b").as_deref(), Some("catch-all"));
+    }
+
+    #[test]
+    fn test_precedence_is_left_to_right() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/a/:x/c", |_| "param first".to_string())
+            .unwrap();
+        storage
+            .add_route("/a/b/:y", |_| "static first".to_string())
+            .unwrap();
+
+        let matched = storage.match_route("/a/b/c").unwrap();
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            "static first"
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/users/:id", |_| "first".to_string())
+            .unwrap();
+
+        assert_eq!(
+            storage.add_route("/users/:id", |_| "second".to_string()),
+            Err(RouteConflict::Duplicate {
+                method: None,
+                path: "/users/:id".to_string()
+            })
+        );
+        assert_eq!(
+            storage.add_route("/users/:name", |_| "second".to_string()),
+            Err(RouteConflict::Ambiguous {
+                method: None,
+                path: "/users/:name".to_string(),
+                existing: "/users/:id".to_string()
+            })
+        );
+        assert!(storage.add_route("/users/me", |_| "me".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_replace_route() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/users/:id", |_| "first".to_string())
+            .unwrap();
+
+        let replaced = storage
+            .replace_route("/users/:name", |_| "second".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(replaced.path, "/users/:id");
+
+        assert_eq!(storage.routes.len(), 1);
+        let matched = storage.match_route("/users/1").unwrap();
+        assert_eq!(matched.params.get("name"), Some("1"));
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            "second"
+        );
+    }
+
+    #[test]
+    fn test_method_routes_keep_their_own_param_names() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_method_route(
+                Some(Method::Get),
+                "/users/:id",
+                with_params(|_, params: &PathParams| params.get("id").unwrap().to_string()),
+            )
+            .unwrap();
+        storage
+            .add_method_route(
+                Some(Method::Put),
+                "/users/:name",
+                with_params(|_, params: &PathParams| params.get("name").unwrap().to_string()),
+            )
+            .unwrap();
+        storage
+            .add_method_route(Some(Method::Get), "/users/me", |_| "me".to_string())
+            .unwrap();
+
+        let handle = |method: Method, path: &str| {
+            storage
+                .match_method_route(Some(&method), path)
+                .and_then(|matched| matched.handle(DashmapDependencyContainer::default()))
+        };
+
+        assert_eq!(handle(Method::Get, "/users/1").unwrap(), "1");
+        assert_eq!(handle(Method::Put, "/users/bob").unwrap(), "bob");
+        assert_eq!(handle(Method::Get, "/users/me").unwrap(), "me");
+        assert!(matches!(
+            handle(Method::Put, "/users/me"),
+            Err(DispatchError::MethodNotAllowed {
+                method: Some(Method::Put),
+                path,
+                allowed,
+            }) if path == "/users/me" && allowed == [Method::Get]
+        ));
+    }
+
+    #[test]
+    fn test_remove_route() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_method_route(Some(Method::Get), "/users/:id", |_| "get".to_string())
+            .unwrap();
+        storage
+            .add_method_route(Some(Method::Delete), "/users/:id", |_| "delete".to_string())
+            .unwrap();
+
+        assert!(storage.remove_route("/users/:id").is_none());
+        assert!(storage.remove(Some(&Method::Get), "/users/:name").is_none());
+
+        let removed = storage.remove(Some(&Method::Get), "/users/:id").unwrap();
+        assert_eq!(removed.method, Some(Method::Get));
+        assert!(matches!(
+            storage.match_method_route(Some(&Method::Get), "/users/1"),
+            Err(DispatchError::MethodNotAllowed { .. })
+        ));
+
+        assert!(storage
+            .remove(Some(&Method::Delete), "/users/:id")
+            .is_some());
+        assert!(storage.routes.is_empty());
+    }
+}
@@ -0,0 +1,716 @@
+use std::collections::HashMap;
+
+use crate::{
+    dependency::container::DependencyContainer,
+    route::{
+        method::Method,
+        path::{
+            params::PathParams,
+            pattern::{split_segments, PathPattern, Segment},
+            RoutePath,
+        },
+        Route,
+    },
+    router::error::DispatchError,
+};
+
+use super::{
+    error::RouteConflict, method::MethodRoutes, missed, not_found, RouteMatch, RouteStorage,
+};
+
+type Leaf<P, O, C> = (PathPattern, Route<P, O, C>);
+
+struct Node<P, O, C>
+where
+    P: RoutePath,
+{
+    prefix: Vec<String>,
+    statics: HashMap<String, Node<P, O, C>>,
+    /// Keys of `statics` by their lowercase form, for case-insensitive lookups.
+    folded: HashMap<String, Vec<String>>,
+    dynamic: Option<Box<Node<P, O, C>>>,
+    catch_all: MethodRoutes<Leaf<P, O, C>>,
+    route: MethodRoutes<Leaf<P, O, C>>,
+}
+
+impl<P, O, C> Clone for Node<P, O, C>
+where
+    P: RoutePath,
+{
+    fn clone(&self) -> Self {
+        Node {
+            prefix: self.prefix.clone(),
+            statics: self.statics.clone(),
+            folded: self.folded.clone(),
+            dynamic: self.dynamic.clone(),
+            catch_all: self.catch_all.clone(),
+            route: self.route.clone(),
+        }
+    }
+}
+
+impl<P, O, C> Node<P, O, C>
+where
+    P: RoutePath,
+{
+    fn new(prefix: Vec<String>) -> Self {
+        Node {
+            prefix,
+            statics: HashMap::new(),
+            folded: HashMap::new(),
+            dynamic: None,
+            catch_all: MethodRoutes::new(),
+            route: MethodRoutes::new(),
+        }
+    }
+
+    fn slot(&mut self, segments: &[Segment]) -> &mut MethodRoutes<Leaf<P, O, C>> {
+        let Some(first) = segments.first() else {
+            return &mut self.route;
+        };
+
+        match first {
+            Segment::Static(value) => {
+                let run: Vec<&str> = segments
+                    .iter()
+                    .map_while(|segment| match segment {
+                        Segment::Static(value) => Some(value.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+
+                if !self.statics.contains_key(value) {
+                    self.folded
+                        .entry(value.to_ascii_lowercase())
+                        .or_default()
+                        .push(value.clone());
+                }
+                let child = self
+                    .statics
+                    .entry(value.clone())
+                    .or_insert_with(|| Node::new(run.iter().map(|s| s.to_string()).collect()));
+
+                let common = child
+                    .prefix
+                    .iter()
+                    .zip(&run)
+                    .take_while(|(a, b)| a == *b)
+                    .count();
+
+                if common < child.prefix.len() {
+                    child.split(common);
+                }
+
+                child.slot(&segments[common..])
+            }
+            Segment::Param(_) | Segment::Wildcard => self
+                .dynamic
+                .get_or_insert_with(|| Box::new(Node::new(Vec::new())))
+                .slot(&segments[1..]),
+            Segment::CatchAll(_) => &mut self.catch_all,
+        }
+    }
+
+    fn find_slot(&mut self, segments: &[Segment]) -> Option<&mut MethodRoutes<Leaf<P, O, C>>> {
+        let Some(first) = segments.first() else {
+            return Some(&mut self.route);
+        };
+
+        match first {
+            Segment::Static(value) => {
+                let child = self.statics.get_mut(value)?;
+                let len = child.prefix.len();
+                let matches = segments.len() >= len
+                    && child.prefix.iter().zip(segments).all(|(a, b)| match b {
+                        Segment::Static(value) => a == value,
+                        _ => false,
+                    });
+
+                if !matches {
+                    return None;
+                }
+                child.find_slot(&segments[len..])
+            }
+            Segment::Param(_) | Segment::Wildcard => {
+                self.dynamic.as_mut()?.find_slot(&segments[1..])
+            }
+            Segment::CatchAll(_) => Some(&mut self.catch_all),
+        }
+    }
+
+    fn collect_routes<'a>(&'a self, routes: &mut Vec<&'a Route<P, O, C>>) {
+        routes.extend(self.route.values().map(|(_, route)| route));
+        routes.extend(self.catch_all.values().map(|(_, route)| route));
+        for child in self.statics.values() {
+            child.collect_routes(routes);
+        }
+        if let Some(child) = &self.dynamic {
+            child.collect_routes(routes);
+        }
+    }
+
+    fn split(&mut self, at: usize) {
+        let tail = self.prefix.split_off(at);
+        let mut child = Node::new(tail);
+        std::mem::swap(&mut child.statics, &mut self.statics);
+        std::mem::swap(&mut child.folded, &mut self.folded);
+        child.dynamic = self.dynamic.take();
+        child.catch_all = std::mem::take(&mut self.catch_all);
+        child.route = std::mem::take(&mut self.route);
+
+        let key = child.prefix[0].clone();
+        self.folded
+            .insert(key.to_ascii_lowercase(), vec![key.clone()]);
+        self.statics.insert(key, child);
+    }
+
+    /// Walks the nodes matching `parts`, most specific first, handing each
+    /// non-empty leaf to `visit` until it returns a result. With
+    /// `ignore_case`, static children differing only in ASCII case are tried
+    /// after the exact one. Parameters never match an empty segment.
+    fn lookup<'a, 'p, R>(
+        &'a self,
+        parts: impl Iterator<Item = &'p str> + Clone,
+        ignore_case: bool,
+        visit: &mut impl FnMut(&'a MethodRoutes<Leaf<P, O, C>>) -> Option<R>,
+    ) -> Option<R> {
+        let mut rest = parts.clone();
+        let Some(first) = rest.next() else {
+            return if self.route.is_empty() {
+                None
+            } else {
+                visit(&self.route)
+            };
+        };
+
+        let exact = self.statics.get_key_value(first);
+        let folded = ignore_case
+            .then(|| self.folded.get(&first.to_ascii_lowercase()))
+            .flatten()
+            .into_iter()
+            .flatten()
+            .filter(|key| key.as_str() != first)
+            .filter_map(|key| self.statics.get_key_value(key));
+
+        for (_, child) in exact.into_iter().chain(folded) {
+            let mut remaining = parts.clone();
+            let matches = child.prefix.iter().all(|segment| {
+                remaining.next().is_some_and(|part| {
+                    segment == part || (ignore_case && segment.eq_ignore_ascii_case(part))
+                })
+            });
+            if matches {
+                if let Some(found) = child.lookup(remaining, ignore_case, visit) {
+                    return Some(found);
+                }
+            }
+        }
+
+        if let Some(child) = self.dynamic.as_ref().filter(|_| !first.is_empty()) {
+            if let Some(found) = child.lookup(rest, ignore_case, visit) {
+                return Some(found);
+            }
+        }
+
+        if self.catch_all.is_empty() {
+            None
+        } else {
+            visit(&self.catch_all)
+        }
+    }
+}
+
+pub struct RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    root: Node<P, O, C>,
+}
+
+impl<P, O, C> Clone for RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn clone(&self) -> Self {
+        RadixStorage {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<P, O, C> Default for RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, O, C> RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    pub fn new() -> Self {
+        RadixStorage {
+            root: Node::new(Vec::new()),
+        }
+    }
+}
+
+impl<P, O, C> RouteStorage<P, O, C> for RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn insert(&mut self, route: Route<P, O, C>) -> Result<(), RouteConflict> {
+        let pattern = route.path.pattern()?;
+        let routes = self.root.slot(pattern.segments());
+
+        if route.is_guarded() {
+            routes.push(route.method.clone(), (pattern, route));
+            return Ok(());
+        }
+
+        if let Some((_, existing)) = routes.get(route.method.as_ref()) {
+            return Err(RouteConflict::new(
+                route.method,
+                route.path.string_repr(),
+                existing.path.string_repr(),
+            ));
+        }
+
+        routes.insert(route.method.clone(), (pattern, route));
+        Ok(())
+    }
+
+    fn replace(&mut self, route: Route<P, O, C>) -> Result<Option<Route<P, O, C>>, RouteConflict> {
+        let pattern = route.path.pattern()?;
+        let routes = self.root.slot(pattern.segments());
+
+        if route.is_guarded() {
+            let name = route.name.clone();
+            return Ok(routes
+                .push_replacing(route.method.clone(), (pattern, route), |(_, existing)| {
+                    name.is_some() && existing.name == name
+                })
+                .map(|(_, route)| route));
+        }
+        Ok(routes
+            .insert_with(route.method.clone(), |replaced| {
+                (pattern, route.inherit(replaced.map(|(_, route)| route)))
+            })
+            .map(|(_, route)| route))
+    }
+
+    fn remove(&mut self, method: Option<&Method>, path: &str) -> Option<Route<P, O, C>> {
+        let pattern = PathPattern::parse(path).ok()?;
+        let routes = self.root.find_slot(pattern.segments())?;
+
+        if routes.get(method)?.0 != pattern {
+            return None;
+        }
+        routes.remove(method).map(|(_, route)| route)
+    }
+
+    fn remove_named(
+        &mut self,
+        method: Option<&Method>,
+        path: &str,
+        name: &str,
+    ) -> Option<Route<P, O, C>> {
+        let pattern = PathPattern::parse(path).ok()?;
+        self.root
+            .find_slot(pattern.segments())?
+            .remove_where(method, |(existing, route)| {
+                *existing == pattern && route.name.as_deref() == Some(name)
+            })
+            .map(|(_, route)| route)
+    }
+
+    fn routes<'a>(&'a self) -> impl Iterator<Item = &'a Route<P, O, C>>
+    where
+        P: 'a,
+        O: 'a,
+        C: 'a,
+    {
+        let mut routes = Vec::new();
+        self.root.collect_routes(&mut routes);
+        routes.into_iter()
+    }
+
+    fn find_route(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.lookup(method, path, false, accept)
+    }
+
+    fn find_route_ignore_case(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        self.lookup(method, path, true, accept)
+    }
+}
+
+impl<P, O, C> RadixStorage<P, O, C>
+where
+    P: RoutePath,
+    C: DependencyContainer,
+{
+    fn lookup(
+        &self,
+        method: Option<&Method>,
+        path: &str,
+        ignore_case: bool,
+        accept: &dyn Fn(&Route<P, O, C>) -> bool,
+    ) -> Result<RouteMatch<'_, P, O, C>, DispatchError> {
+        // The walk only records which leaf matched; its pattern then captures
+        // the params, so nothing is allocated unless there are params.
+        let capture: fn(&PathPattern, &str) -> Option<PathParams> = if ignore_case {
+            PathPattern::matches_ignore_case
+        } else {
+            PathPattern::matches
+        };
+
+        self.root
+            .lookup(
+                split_segments(path),
+                ignore_case,
+                &mut |routes| match routes.select_where(method, |(_, route)| accept(route)) {
+                    Ok((pattern, route)) => Some(
+                        capture(pattern, path)
+                            .map(|params| RouteMatch::new(route, params))
+                            .ok_or_else(|| not_found(path)),
+                    ),
+                    Err(miss) => missed(miss, method, path).map(Err),
+                },
+            )
+            .unwrap_or_else(|| Err(not_found(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dependency::container::dashmap::DashmapDependencyContainer,
+        route::{handler::with_params, path::params::PathParams},
+    };
+
+    use super::*;
+
+    type TestStorage = RadixStorage<String, String, DashmapDependencyContainer>;
+
+    fn dispatch(storage: &TestStorage, path: &str) -> Option<String> {
+        storage
+            .match_route(path)
+            .and_then(|matched| matched.handle(DashmapDependencyContainer::default()).ok())
+    }
+
+    #[test]
+    fn test_add_and_match_static_route() {
+        let mut storage = TestStorage::new();
+        storage.add_route("/test", |_| "test".to_string()).unwrap();
+        storage.add_route("/", |_| "root".to_string()).unwrap();
+
+        assert_eq!(dispatch(&storage, "/test").as_deref(), Some("test"));
+        assert_eq!(dispatch(&storage, "/").as_deref(), Some("root"));
+        assert!(storage.match_route("/nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_compressed_prefix_is_split() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/api/v1/users", |_| "users".to_string())
+            .unwrap();
+        storage
+            .add_route("/api/v1/posts", |_| "posts".to_string())
+            .unwrap();
+        storage.add_route("/api", |_| "api".to_string()).unwrap();
+
+        assert_eq!(storage.root.statics["api"].prefix, ["api"]);
+        assert!(!storage.root.statics["api"].route.is_empty());
+        assert_eq!(
+            dispatch(&storage, "/api/v1/users").as_deref(),
+            Some("users")
+        );
+        assert_eq!(
+            dispatch(&storage, "/api/v1/posts").as_deref(),
+            Some("posts")
+        );
+        assert_eq!(dispatch(&storage, "/api").as_deref(), Some("api"));
+        assert!(storage.match_route("/api/v1").is_none());
+    }
+
+    #[test]
+    fn test_ignore_case_uses_folded_keys() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/API/v1/Users", |_| "users".to_string())
+            .unwrap();
+        storage.add_route("/API/v2", |_| "v2".to_string()).unwrap();
+        storage.add_route("/api", |_| "api".to_string()).unwrap();
+
+        assert_eq!(storage.root.folded["api"], ["API", "api"]);
+        let find = |path| {
+            storage
+                .find_route_ignore_case(None, path, &|_| true)
+                .map(|matched| matched.route.path.clone())
+                .ok()
+        };
+        assert_eq!(find("/api/V1/users").as_deref(), Some("/API/v1/Users"));
+        assert_eq!(find("/Api/V2").as_deref(), Some("/API/v2"));
+        assert_eq!(find("/api").as_deref(), Some("/api"));
+        assert!(storage
+            .find_route(None, "/api/v1/users", &|_| true)
+            .is_err());
+    }
+
+    #[test]
+    fn test_match_captures_params() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route(
+                "/users/:id/posts/:post_id",
+                with_params(|_, params: &PathParams| {
+                    format!(
+                        "{}:{}",
+                        params.get("id").unwrap(),
+                        params.get("post_id").unwrap()
+                    )
+                }),
+            )
+            .unwrap();
+        storage
+            .add_route(
+                "/users/:user/*rest",
+                with_params(|_, params: &PathParams| {
+                    format!(
+                        "{}:{}",
+                        params.get("user").unwrap(),
+                        params.get("rest").unwrap()
+                    )
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(
+            dispatch(&storage, "/users/42/posts/7").as_deref(),
+            Some("42:7")
+        );
+        assert_eq!(
+            dispatch(&storage, "/users/42/files/a/b").as_deref(),
+            Some("42:files/a/b")
+        );
+        assert!(storage.match_route("/users/42").is_none());
+    }
+
+    #[test]
+    fn test_precedence_with_backtracking() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/files/*rest", |_| "catch-all".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/:name", |_| "param".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/index", |_| "static".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/index/raw", |_| "static raw".to_string())
+            .unwrap();
+        storage
+            .add_route("/files/:name/meta", |_| "param meta".to_string())
+            .unwrap();
+
+        assert_eq!(
+            dispatch(&storage, "/files/index").as_deref(),
+            Some("static")
+        );
+        assert_eq!(
+            dispatch(&storage, "/files/readme").as_deref(),
+            Some("param")
+        );
+        assert_eq!(
+            dispatch(&storage, "/files/index/raw").as_deref(),
+            Some("static raw")
+        );
+        assert_eq!(
+            dispatch(&storage, "/files/index/meta").as_deref(),
+            Some("param meta")
+        );
+        assert_eq!(
+            dispatch(&storage, "/files/index/other").as_deref(),
+            Some("catch-all")
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut storage = TestStorage::new();
+        storage.add_route("/a/:x", |_| "x".to_string()).unwrap();
+        storage
+            .add_route("/a/*rest", |_| "rest".to_string())
+            .unwrap();
+
+        assert_eq!(
+            storage.add_route("/a/:y", |_| "y".to_string()),
+            Err(RouteConflict::Ambiguous {
+                method: None,
+                path: "/a/:y".to_string(),
+                existing: "/a/:x".to_string()
+            })
+        );
+        assert_eq!(
+            storage.add_route("/a/*rest", |_| "rest".to_string()),
+            Err(RouteConflict::Duplicate {
+                method: None,
+                path: "/a/*rest".to_string()
+            })
+        );
+
+        let replaced = storage
+            .replace_route("/a/:y", |_| "y".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(replaced.path, "/a/:x");
+
+        let matched = storage.match_route("/a/1").unwrap();
+        assert_eq!(matched.params.get("y"), Some("1"));
+        assert_eq!(
+            matched
+                .handle(DashmapDependencyContainer::default())
+                .unwrap(),
+            "y"
+        );
+    }
+
+    #[test]
+    fn test_many_routes() {
+        let mut storage = TestStorage::new();
+        for i in 0..1000 {
+            storage
+                .add_route(format!("/items/{i}/detail"), move |_| i.to_string())
+                .unwrap();
+        }
+
+        assert_eq!(
+            dispatch(&storage, "/items/999/detail").as_deref(),
+            Some("999")
+        );
+        assert!(storage.match_route("/items/1000/detail").is_none());
+    }
+
+    #[test]
+    fn test_method_routes() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_method_route(Some(Method::Get), "/files/:name", |_| "get".to_string())
+            .unwrap();
+        storage
+            .add_method_route(Some(Method::Delete), "/files/:id", |_| "delete".to_string())
+            .unwrap();
+        storage
+            .add_method_route(None, "/files/*rest", |_| "any".to_string())
+            .unwrap();
+        assert!(storage
+            .add_method_route(Some(Method::Get), "/files/:other", |_| "dup".to_string())
+            .is_err());
+
+        let handle = |method: Method, path: &str| {
+            storage
+                .match_method_route(Some(&method), path)
+                .and_then(|matched| matched.handle(DashmapDependencyContainer::default()))
+        };
+
+        assert_eq!(handle(Method::Get, "/files/a").unwrap(), "get");
+        assert_eq!(handle(Method::Delete, "/files/a").unwrap(), "delete");
+        assert_eq!(handle(Method::Post, "/files/a/b").unwrap(), "any");
+        assert!(matches!(
+            handle(Method::Post, "/files/a"),
+            Err(DispatchError::MethodNotAllowed {
+                method: Some(Method::Post),
+                path,
+                allowed,
+            }) if path == "/files/a" && allowed == [Method::Get, Method::Delete]
+        ));
+    }
+
+    #[test]
+    fn test_remove_route() {
+        let mut storage = TestStorage::new();
+        storage
+            .add_route("/api/users", |_| "users".to_string())
+            .unwrap();
+        storage
+            .add_route("/api/users/:id", |_| "user".to_string())
+            .unwrap();
+        storage
+            .add_route("/api/*rest", |_| "rest".to_string())
+            .unwrap();
+
+        assert!(storage.remove_route("/api/users/:name").is_none());
+        assert!(storage.remove_route("/api/missing").is_none());
+
+        let removed = storage.remove_route("/api/users/:id").unwrap();
+        assert_eq!(removed.path, "/api/users/:id");
+        assert_eq!(dispatch(&storage, "/api/users/1").as_deref(), Some("rest"));
+        assert_eq!(dispatch(&storage, "/api/users").as_deref(), Some("users"));
+
+        assert!(storage.remove_route("/api/*rest").is_some());
+        assert_eq!(dispatch(&storage, "/api/users/1"), None);
+
+        storage
+            .add_route("/api/users/:id", |_| "again".to_string())
+            .unwrap();
+        assert_eq!(dispatch(&storage, "/api/users/1").as_deref(), Some("again"));
+    }
+
+    #[test]
+    fn test_routes() {
+        let mut storage = TestStorage::new();
+        for path in ["/", "/api/users", "/api/users/:id", "/api/*rest", "/apis"] {
+            storage.add_route(path, |_| String::new()).unwrap();
+        }
+
+        let mut paths: Vec<&str> = storage.routes().map(|route| route.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            ["/", "/api/*rest", "/api/users", "/api/users/:id", "/apis"]
+        );
+    }
+
+    #[test]
+    fn test_rejected_routes_fall_through() {
+        let mut storage = TestStorage::new();
+        storage
+            .insert(Route::new("/users/me", |_| "me".to_string()).with_guard(|_: &_, _: &_| false))
+            .unwrap();
+        storage
+            .add_route("/users/:id", |_| "user".to_string())
+            .unwrap();
+
+        let reject_me =
+            |route: &Route<String, String, DashmapDependencyContainer>| route.path != "/users/me";
+        let matched = storage.find_route(None, "/users/me", &reject_me).unwrap();
+        assert_eq!(matched.route.path, "/users/:id");
+        assert_eq!(matched.params.get("id"), Some("me"));
+
+        assert!(matches!(
+            storage.find_route(None, "/users/me", &|_| false),
+            Err(DispatchError::NotFound { path }) if path == "/users/me"
+        ));
+    }
+}